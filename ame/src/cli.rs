@@ -0,0 +1,90 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// ame QQ Bot
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// 配置文件路径
+    #[arg(short, long, global = true, env = "NBRS_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// 日志文件目录
+    #[arg(long, global = true, env = "AME_LOG_DIR", default_value = "logs")]
+    pub log_dir: PathBuf,
+    /// 终端日志格式
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+    /// 日志文件轮转周期
+    #[arg(long, global = true, value_enum, default_value_t = LogRotation::Hourly)]
+    pub log_rotation: LogRotation,
+    /// 日志级别，格式同 RUST_LOG，如 `info,nonebot_rs=debug`
+    #[arg(long, global = true, env = "RUST_LOG", default_value = "info")]
+    pub log_level: String,
+    /// 启用 Plugin，可重复或以逗号分隔，覆盖配置文件中的 `enabled`
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub enable: Vec<String>,
+    /// 禁用 Plugin，可重复或以逗号分隔，覆盖配置文件中的 `enabled`
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub disable: Vec<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// 运行 Bot（默认）
+    Run,
+    /// 校验配置文件与 Plugin 配置
+    CheckConfig,
+    /// 输出包含所有 Plugin 默认配置的配置文件
+    PrintDefaultConfig,
+    /// 执行数据库迁移
+    #[cfg(feature = "msg-saver")]
+    Migrate,
+    /// 列出所有 Plugin 及其启用状态
+    ListPlugins,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Pretty,
+    Compact,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl From<LogRotation> for tracing_appender::rolling::Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        match rotation {
+            LogRotation::Minutely => Self::MINUTELY,
+            LogRotation::Hourly => Self::HOURLY,
+            LogRotation::Daily => Self::DAILY,
+            LogRotation::Never => Self::NEVER,
+        }
+    }
+}
+
+#[test]
+fn cli_test() {
+    use clap::CommandFactory;
+    Cli::command().debug_assert();
+
+    let cli = Cli::parse_from([
+        "ame",
+        "list-plugins",
+        "--disable",
+        "Moli,MsgSaver",
+        "--log-format",
+        "json",
+    ]);
+    assert_eq!(cli.command, Some(Command::ListPlugins));
+    assert_eq!(cli.disable, ["Moli", "MsgSaver"]);
+    assert_eq!(cli.log_format, LogFormat::Json);
+}
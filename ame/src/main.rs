@@ -1,34 +1,222 @@
-use ame::plugins::{moli::Moli, msg_saver::MsgSaver};
-use tracing_appender::{non_blocking, rolling};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
+mod cli;
+
+#[cfg(feature = "msg-saver")]
+use ame::plugins::msg_saver::{MsgSaver, MsgSaverConfig};
+use ame::plugins::registry::{register, Registry};
+use clap::Parser;
+use cli::{Cli, Command, LogFormat};
+use nonebot_rs::config::NoneBotConfig;
+use std::process::ExitCode;
+use tracing::{event, Level};
+use tracing_appender::{non_blocking::WorkerGuard, rolling::RollingFileAppender};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry as Subscriber,
+};
 
 #[global_allocator]
 static ALLOC: snmalloc_rs::SnMalloc = snmalloc_rs::SnMalloc;
 
 #[tokio::main]
-async fn main() {
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    let formatting_layer = fmt::layer().pretty().with_writer(std::io::stderr);
-    let file_appender = rolling::hourly("logs", "ame.log");
-    let (non_blocking_appender, _guard) = non_blocking(file_appender);
-    let file_layer = fmt::layer()
-        .with_ansi(false)
-        .with_writer(non_blocking_appender);
-    Registry::default()
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Run);
+    let _guard = init_tracing(&cli, command == Command::Run);
+
+    let config = || {
+        let path = cli
+            .config
+            .clone()
+            .unwrap_or_else(NoneBotConfig::default_path);
+        NoneBotConfig::load_from(path).map_err(|e| event!(Level::ERROR, "{}", e))
+    };
+    match command {
+        Command::Run => {
+            let Ok(config) = config() else {
+                return ExitCode::FAILURE;
+            };
+            let mut nonebot = nonebot_rs::Nonebot::with_config(config);
+            let mut registry = Registry::run(&mut nonebot);
+            apply_overrides(&cli, &mut registry);
+            register(&mut registry);
+            if !check_overrides(&registry) {
+                return ExitCode::FAILURE;
+            }
+            for entry in registry.entries().iter().filter(|e| !e.enabled) {
+                event!(Level::INFO, "Plugin {} is disabled", entry.info.name);
+            }
+            nonebot.run().await;
+            ExitCode::SUCCESS
+        }
+        Command::CheckConfig => {
+            let Ok(config) = config() else {
+                return ExitCode::FAILURE;
+            };
+            let path = config.path().display().to_string();
+            let mut registry = Registry::inspect(config);
+            register(&mut registry);
+            let mut valid = true;
+            for entry in registry.entries() {
+                if let Some(e) = &entry.error {
+                    event!(Level::ERROR, "[plugins.{}] {}", entry.info.name, e);
+                    valid = false;
+                }
+            }
+            if !valid {
+                return ExitCode::FAILURE;
+            }
+            event!(Level::INFO, "{} is valid", path);
+            ExitCode::SUCCESS
+        }
+        Command::ListPlugins => {
+            let Ok(config) = config() else {
+                return ExitCode::FAILURE;
+            };
+            let mut registry = Registry::inspect(config);
+            apply_overrides(&cli, &mut registry);
+            register(&mut registry);
+            if !check_overrides(&registry) {
+                return ExitCode::FAILURE;
+            }
+            for entry in registry.entries() {
+                println!(
+                    "{:<10} {:<8} {:<8} {} {}",
+                    entry.info.name,
+                    if entry.enabled { "enabled" } else { "disabled" },
+                    entry.info.version,
+                    entry.info.id,
+                    entry.info.desc
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        #[cfg(feature = "msg-saver")]
+        Command::Migrate => match config() {
+            Ok(config) => migrate(&config).await,
+            Err(()) => ExitCode::FAILURE,
+        },
+        Command::PrintDefaultConfig => print_default_config(),
+    }
+}
+
+/// 初始化日志，`file` 为 true 时同时写入日志文件
+///
+/// JSON 格式下 Event 字段平铺至顶层，并附带所在 span。
+fn init_tracing(cli: &Cli, file: bool) -> Option<WorkerGuard> {
+    let env_filter = EnvFilter::try_new(&cli.log_level).unwrap_or_else(|e| {
+        eprintln!("Invalid log level `{}`: {}", cli.log_level, e);
+        EnvFilter::new("info")
+    });
+    let stderr = fmt::layer().with_writer(std::io::stderr);
+    let stderr_layer = match cli.log_format {
+        LogFormat::Pretty => stderr.pretty().boxed(),
+        LogFormat::Compact => stderr.compact().boxed(),
+        LogFormat::Json => stderr.json().flatten_event(true).boxed(),
+    };
+    let (file_layer, guard) = if file {
+        let appender = RollingFileAppender::new(cli.log_rotation.into(), &cli.log_dir, "ame.log");
+        let (non_blocking_appender, guard) = tracing_appender::non_blocking(appender);
+        let layer = fmt::layer()
+            .with_ansi(false)
+            .with_writer(non_blocking_appender);
+        let layer = match cli.log_format {
+            LogFormat::Json => layer.json().flatten_event(true).boxed(),
+            _ => layer.boxed(),
+        };
+        (Some(layer), Some(guard))
+    } else {
+        (None, None)
+    };
+    Subscriber::default()
         .with(env_filter)
-        .with(formatting_layer)
+        .with(stderr_layer)
         .with(file_layer)
         .init();
-    let mut nonebot = nonebot_rs::Nonebot::new();
-    let mut matchers = nonebot_rs::Matchers::new_empty();
-    let moli = Moli::new();
-    matchers
-        .add_message_matcher(nonebot_rs::builtin::bot_status::bot_status())
-        .add_message_matcher(ame::matchers::lolicon::lolicon());
-    nonebot
-        .add_plugin(nonebot_rs::Logger)
-        .add_plugin(matchers)
-        .add_plugin(moli)
-        .add_plugin(MsgSaver);
-    nonebot.run().await
+    guard
+}
+
+fn apply_overrides(cli: &Cli, registry: &mut Registry) {
+    for name in &cli.enable {
+        registry.set_enabled(name, true);
+    }
+    for name in &cli.disable {
+        registry.set_enabled(name, false);
+    }
+}
+
+/// `--enable`、`--disable` 中的名称均须对应已注册的 Plugin
+fn check_overrides(registry: &Registry) -> bool {
+    let unknown = registry.unknown_overrides();
+    for name in &unknown {
+        event!(
+            Level::ERROR,
+            plugin = name,
+            "Unknown plugin in --enable/--disable, not registered or not compiled in"
+        );
+    }
+    unknown.is_empty()
+}
+
+fn print_default_config() -> ExitCode {
+    let mut config = NoneBotConfig::default();
+    let mut registry = Registry::inspect(config.clone());
+    register(&mut registry);
+    for entry in registry.entries() {
+        if let Some(default) = &entry.default_config {
+            config
+                .plugins
+                .insert(entry.info.name.to_string(), default.clone());
+        }
+    }
+    match toml::to_string(&config) {
+        Ok(config) => {
+            print!("{}", config);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            event!(Level::ERROR, "Serialize default config fail: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 使用 `[plugins.MsgSaver]` 中的数据库执行 migrations 目录下的迁移
+#[cfg(feature = "msg-saver")]
+async fn migrate(config: &NoneBotConfig) -> ExitCode {
+    use nonebot_rs::Plugin;
+    use sqlx::postgres::PgPoolOptions;
+
+    let name = MsgSaver::default().plugin_info().name;
+    let db_config: MsgSaverConfig = match config.plugin_section(name) {
+        Some(section) => match section.clone().try_into() {
+            Ok(db_config) => db_config,
+            Err(e) => {
+                event!(Level::ERROR, "[plugins.{}] {}", name, e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => MsgSaverConfig::default(),
+    };
+    let pool = match PgPoolOptions::new()
+        .max_connections(1)
+        .connect(db_config.database_url.expose())
+        .await
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            event!(Level::ERROR, "Database connect fault: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let result = sqlx::migrate!("../migrations").run(&pool).await;
+    pool.close().await;
+    match result {
+        Ok(()) => {
+            event!(Level::INFO, "Database migrated.");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            event!(Level::ERROR, "Database migrate fail: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,17 +1,25 @@
 use nonebot_rs::builtin::matcher::prelude::*;
 
-#[derive(Clone)]
-pub struct DriftingBottle;
-
-#[async_trait]
-impl Handler<MessageEvent> for DriftingBottle {
-    on_command!(MessageEvent, "漂流瓶");
-    async fn handle(&self, _event: MessageEvent, matcher: Matcher<MessageEvent>) {
-        matcher.send_text("測試...").await;
-    }
+async fn drifting_bottle_handler(reply: Reply) {
+    reply.text("測試...").await;
 }
 
 pub fn drifting_bottle() -> Matcher<MessageEvent> {
-    Matcher::new("DriftingBottle", DriftingBottle)
-        .add_pre_matcher(prematchers::option_command_start())
+    Matcher::new(
+        "DriftingBottle",
+        FnHandler::new(drifting_bottle_handler).command(&["漂流瓶"]),
+    )
+    .add_pre_matcher(prematchers::option_command_start())
+}
+
+#[tokio::test]
+async fn drifting_bottle_test() {
+    use nonebot_rs::testing::{MessageBuilder, TestBot};
+
+    let mut matchers = nonebot_rs::Matchers::new_empty();
+    matchers.add_message_matcher(drifting_bottle());
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+    bot.send_message(MessageBuilder::group("1", "2", "漂流瓶").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("測試..."));
 }
@@ -8,49 +8,42 @@ use serde::Deserialize;
 
 const API_URL: &str = "https://api.lolicon.app/setu/v2";
 
-#[derive(Clone)]
-pub struct Lolicon;
-
-impl Lolicon {
-    async fn get_api() -> Result<LoliconApi, reqwest::Error> {
-        let mut url = Url::from_str(API_URL).unwrap();
-        url.set_query(Some("num=1"));
-        reqwest::get(url).await?.json::<LoliconApi>().await
-    }
+async fn get_api() -> Result<LoliconApi, reqwest::Error> {
+    let mut url = Url::from_str(API_URL).unwrap();
+    url.set_query(Some("num=1"));
+    reqwest::get(url).await?.json::<LoliconApi>().await
+}
 
-    async fn make_message() -> Result<Vec<Vec<Message>>, reqwest::Error> {
-        let maker = |api: &LoliconData| {
-            let img = Message::Image {
-                file: api.urls.get("original").unwrap().to_owned(),
-                ty: None,
-                url: None,
-                cache: None,
-                proxy: None,
-                timeout: Some(60),
-            };
-            let title = Message::Text {
-                text: format!("{}\n", api.title),
-            };
-            let author = Message::Text {
-                text: format!("作者: {}\n", api.author),
-            };
-            let uid = Message::Text {
-                text: format!("uid: {}", api.uid),
-            };
-            vec![img, title, author, uid]
+async fn make_message() -> Result<Vec<Vec<Message>>, reqwest::Error> {
+    let maker = |api: &LoliconData| {
+        let img = Message::Image {
+            file: api.urls.get("original").unwrap().to_owned(),
+            ty: None,
+            url: None,
+            cache: None,
+            proxy: None,
+            timeout: Some(60),
+        };
+        let title = Message::Text {
+            text: format!("{}\n", api.title),
+        };
+        let author = Message::Text {
+            text: format!("作者: {}\n", api.author),
+        };
+        let uid = Message::Text {
+            text: format!("uid: {}", api.uid),
         };
+        vec![img, title, author, uid]
+    };
 
-        let msg: Vec<Vec<Message>> = Self::get_api().await?.data.iter().map(maker).collect();
+    let msg: Vec<Vec<Message>> = get_api().await?.data.iter().map(maker).collect();
 
-        Ok(msg)
-    }
+    Ok(msg)
 }
 
-#[async_trait]
-impl Handler<MessageEvent> for Lolicon {
-    on_command!(
-        MessageEvent,
-        "lolicon",
+#[nonebot_rs::command(
+    "lolicon",
+    aliases = [
         "Lolicon",
         "loli",
         "Loli",
@@ -61,26 +54,25 @@ impl Handler<MessageEvent> for Lolicon {
         "萝莉",
         "来点色图",
         "来点涩图"
-    );
-    async fn handle(&self, _event: MessageEvent, matcher: Matcher<MessageEvent>) {
-        matcher.send_text("正在装填弹药...").await;
-        match Self::make_message().await {
-            Ok(msgs) => {
-                for msg in msgs {
-                    matcher.send(msg).await
-                }
-            }
-            _ => {
-                matcher.send_text("装填失败").await;
+    ],
+    option_command_start = true
+)]
+pub async fn lolicon(reply: Reply) -> HandlerResult {
+    reply.text("正在装填弹药...").await;
+    match make_message().await {
+        Ok(msgs) => {
+            for msg in msgs {
+                reply.send(msg).await
             }
+            Ok(Flow::Stop)
+        }
+        Err(e) => {
+            reply.text("装填失败").await;
+            Err(e.into())
         }
     }
 }
 
-pub fn lolicon() -> Matcher<MessageEvent> {
-    Matcher::new("Lolicon", Lolicon).add_pre_matcher(prematchers::option_command_start())
-}
-
 #[derive(Deserialize, Debug)]
 struct LoliconApi {
     error: String,
@@ -107,9 +99,10 @@ struct LoliconData {
 }
 
 #[tokio::test]
+#[ignore = "requires network"]
 async fn test_get_api() {
-    let api = Lolicon::get_api().await.unwrap();
-    let msg = Lolicon::make_message().await.unwrap();
+    let api = get_api().await.unwrap();
+    let msg = make_message().await.unwrap();
     println!("{:#?}", api);
     println!("{:#?}", msg);
 }
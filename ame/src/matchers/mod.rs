@@ -1,2 +1,3 @@
 pub mod drifting_bottle;
+#[cfg(feature = "lolicon")]
 pub mod lolicon;
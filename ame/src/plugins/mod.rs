@@ -1,2 +1,5 @@
+#[cfg(feature = "moli")]
 pub mod moli;
+#[cfg(feature = "msg-saver")]
 pub mod msg_saver;
+pub mod registry;
@@ -2,22 +2,43 @@ use std::collections::HashMap;
 
 use moli_sdk::{Moli as MoliSdk, MoliReqestParameter};
 use nonebot_rs::{
-    event::{Event, MessageEvent, SelfId},
-    Bot, BotGetter, EventReceiver, Message,
+    event::{Event, MessageEvent, NoneBotEvent, SelfId},
+    Bot, BotGetter, DropPolicy, EventReceiver, EventStream, Message, Secret,
 };
+use serde::{Deserialize, Serialize};
 use tokio::{task::JoinHandle, time::Instant};
-use uuid::{uuid, Uuid};
+use tracing::{event, Level};
 
-pub const PLUGIN_NAME: &str = "Moli";
-pub const PLUGIN_AUTHER: &str = "YosakuraTohu";
-pub const PLUGIN_VERSION: &str = "v0.0.0";
-pub const PLUGIN_DESC: &str = "moli";
-pub const PLUGIN_ID: Uuid = uuid!("467c481f-d34e-456b-8111-0eab92990f46");
+/// Moli 配置，对应 `[plugins.Moli]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoliConfig {
+    /// 茉莉云 Api Key，如 `env:MOLI_API_KEY`，未设置时不回复
+    pub api_key: Secret,
+    pub api_secret: Secret,
+    /// 群聊中触发回复的关键词
+    pub trigger: String,
+    /// 发送给茉莉云的 Bot 昵称
+    pub nickname: String,
+    /// 群聊触发后持续回复的秒数
+    pub timeout: u64,
+}
 
-static MOLI: MoliSdk = MoliSdk::new("ulpxdy2moi9ya4fm", "92kqb4ye");
+impl Default for MoliConfig {
+    fn default() -> Self {
+        MoliConfig {
+            api_key: Secret::default(),
+            api_secret: Secret::default(),
+            trigger: "小雨".to_string(),
+            nickname: "雨".to_string(),
+            timeout: 30,
+        }
+    }
+}
 
 #[derive(Default, Debug, Clone)]
 pub struct Moli {
+    config: MoliConfig,
     bot_getter: Option<BotGetter>,
     group_timeout: HashMap<String, Instant>,
 }
@@ -27,18 +48,48 @@ impl Moli {
         Self::default()
     }
 
-    async fn event_recv(mut self, mut event_receiver: EventReceiver) {
-        while let Ok(event) = event_receiver.recv().await {
+    async fn event_recv(mut self, event_receiver: EventReceiver) {
+        // 等待 HTTP 响应时积压的消息过旧，无需回复
+        let mut events = EventStream::bounded("Moli", event_receiver, 64, DropPolicy::DropOldest);
+        let mut sdk = self.sdk();
+        while let Some(event) = events.recv().await {
+            if let Event::Nonebot(NoneBotEvent::PluginConfigChanged { name, config }) = &event {
+                if name.eq_ignore_ascii_case("Moli") {
+                    match config.clone().map(|c| c.try_into()).transpose() {
+                        Ok(config) => {
+                            self.config = config.unwrap_or_default();
+                            sdk = self.sdk();
+                            event!(Level::INFO, "Moli config reloaded.");
+                        }
+                        Err(e) => event!(Level::ERROR, "Parse Moli config fail: {}", e),
+                    }
+                }
+                continue;
+            }
             let bots = self.bot_getter.clone().unwrap().borrow().clone();
-            if let Some(bot) = bots.get(&event.get_self_id()) {
+            if let (Some(bot), Some(sdk)) = (bots.get(&event.get_self_id()), &sdk) {
                 if let Event::Message(m) = &event {
-                    self.message_handler(m, bot).await;
+                    self.message_handler(m, bot, sdk).await;
                 }
             }
         }
     }
 
-    async fn message_handler(&mut self, event: &MessageEvent, bot: &Bot) {
+    fn sdk(&self) -> Option<MoliSdk> {
+        if self.config.api_key.is_empty() || self.config.api_secret.is_empty() {
+            event!(
+                Level::WARN,
+                "Moli api_key or api_secret is not configured, Moli will not reply."
+            );
+            return None;
+        }
+        Some(MoliSdk::new(
+            self.config.api_key.expose(),
+            self.config.api_secret.expose(),
+        ))
+    }
+
+    async fn message_handler(&mut self, event: &MessageEvent, bot: &Bot, sdk: &MoliSdk) {
         match &event {
             MessageEvent::Private(p) => {
                 let msg = MoliReqestParameter::new(
@@ -47,9 +98,9 @@ impl Moli {
                     p.sender.nickname.clone(),
                     p.sender.user_id.clone(),
                     p.self_id.clone(),
-                    "雨".to_string(),
+                    self.config.nickname.clone(),
                 );
-                if let Ok(res) = MOLI.get_response(msg).await {
+                if let Ok(res) = sdk.get_response(msg).await {
                     if let Some(mss) = res.data {
                         let tm: String = mss
                             .into_iter()
@@ -68,13 +119,13 @@ impl Moli {
             MessageEvent::Group(g) => {
                 let mut timeout = true;
                 if let Some(gt) = self.group_timeout.get(&g.group_id) {
-                    if gt.elapsed().as_secs() < 30 {
+                    if gt.elapsed().as_secs() < self.config.timeout {
                         timeout = false
                     } else {
                         self.group_timeout.remove(&g.group_id);
                     }
                 }
-                if g.raw_message.contains("小雨") || !timeout {
+                if g.raw_message.contains(&self.config.trigger) || !timeout {
                     self.group_timeout
                         .insert(g.group_id.clone(), Instant::now());
                     let msg = MoliReqestParameter::new(
@@ -83,9 +134,9 @@ impl Moli {
                         g.sender.nickname.clone(),
                         g.sender.user_id.clone(),
                         g.self_id.clone(),
-                        "雨".to_string(),
+                        self.config.nickname.clone(),
                     );
-                    if let Ok(res) = MOLI.get_response(msg).await {
+                    if let Ok(res) = sdk.get_response(msg).await {
                         if let Some(mss) = res.data {
                             let tm: String = mss
                                 .into_iter()
@@ -106,20 +157,17 @@ impl Moli {
     }
 }
 
+#[nonebot_rs::plugin(id = "467c481f-d34e-456b-8111-0eab92990f46", desc = "moli")]
 impl nonebot_rs::Plugin for Moli {
+    type Config = MoliConfig;
+
     fn load(&self, event_receiver: EventReceiver, bot_getter: BotGetter) -> JoinHandle<()> {
         let mut moli = self.clone();
         moli.bot_getter = Some(bot_getter.clone());
         tokio::spawn(moli.event_recv(event_receiver))
     }
 
-    fn plugin_info(&self) -> nonebot_rs::plugin::PluginInfo {
-        nonebot_rs::plugin::PluginInfo {
-            name: PLUGIN_NAME,
-            author: PLUGIN_AUTHER,
-            version: PLUGIN_VERSION,
-            desc: PLUGIN_DESC,
-            id: PLUGIN_ID,
-        }
+    fn set_config(&mut self, config: MoliConfig) {
+        self.config = config;
     }
 }
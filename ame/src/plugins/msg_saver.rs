@@ -1,42 +1,93 @@
 use ame_models::prelude::*;
+use nonebot_rs::metrics::prometheus::{register_histogram_vec, HistogramVec};
 use nonebot_rs::{
-    event::{Event, MessageEvent},
-    BotGetter, EventReceiver, Message,
+    event::{Event, MessageEvent, NoneBotEvent},
+    state::StateMap,
+    BotGetter, EventReceiver, EventStream, Message, Secret,
 };
-use sqlx::postgres::PgPoolOptions;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tracing::{event, Level};
-use uuid::{uuid, Uuid};
 
-pub const PLUGIN_NAME: &str = "MsgSaver";
-pub const PLUGIN_AUTHER: &str = "YosakuraTohu";
-pub const PLUGIN_VERSION: &str = "v0.0.0";
-pub const PLUGIN_DESC: &str = "Message saver";
-pub const PLUGIN_ID: Uuid = uuid!("318f9313-a605-498b-9ee3-b5c63f059a24");
+/// MsgSaver 配置，对应 `[plugins.MsgSaver]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MsgSaverConfig {
+    /// 支持 `env:`、`file:` 引用，如 `env:DATABASE_URL`
+    pub database_url: Secret,
+    pub max_connections: u32,
+}
 
-#[derive(Debug, Clone)]
-pub struct MsgSaver;
+impl Default for MsgSaverConfig {
+    fn default() -> Self {
+        MsgSaverConfig {
+            database_url: Secret::new("postgres://localhost/Services"),
+            max_connections: 5,
+        }
+    }
+}
+
+/// 优先使用 Nonebot 共享状态中的 `PgPool`，不存在时按配置连接并存入共享状态
+#[derive(Debug, Clone, Default)]
+pub struct MsgSaver {
+    config: MsgSaverConfig,
+    state: StateMap,
+    /// 事件循环运行时持有，关闭时等待进行中的写入完成
+    running: Arc<Mutex<()>>,
+    /// 已开始关闭，之后连接成功的事件循环不再写入
+    closing: Arc<AtomicBool>,
+}
 
 impl MsgSaver {
-    async fn event_recv(self, mut event_receiver: EventReceiver) {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect("postgres://localhost/Services")
-            .await;
-        while let Ok(event) = event_receiver.recv().await {
-            if let Ok(p) = &pool {
-                if let Event::Message(m) = &event {
-                    message_handler(m, p).await;
-                }
-            } else {
-                event!(Level::ERROR, "Database connect fault.");
-                break;
+    async fn event_recv(self, event_receiver: EventReceiver) {
+        // 连接数据库期间没有待写入的消息，无需阻塞关闭
+        let Some(pool) = self.pool().await else {
+            return;
+        };
+        // 先持有锁再检查，`shutdown` 先设置标志再等待锁，二者不会错过
+        let _running = self.running.lock().await;
+        if self.closing.load(Ordering::Acquire) {
+            return;
+        }
+        let mut events = EventStream::new("MsgSaver", event_receiver);
+        while let Some(event) = events.recv().await {
+            match &event {
+                Event::Message(m) => message_handler(m, &pool).await,
+                Event::Nonebot(NoneBotEvent::Shutdown) => break,
+                _ => {}
             }
         }
     }
 }
 
-async fn message_handler(event: &MessageEvent, pool: &sqlx::PgPool) {
+impl MsgSaver {
+    async fn pool(&self) -> Option<PgPool> {
+        if let Some(pool) = self.state.get::<PgPool>() {
+            return Some((*pool).clone());
+        }
+        match PgPoolOptions::new()
+            .max_connections(self.config.max_connections)
+            .connect(self.config.database_url.expose())
+            .await
+        {
+            Ok(pool) => {
+                self.state.insert(pool.clone());
+                Some(pool)
+            }
+            Err(e) => {
+                event!(Level::ERROR, "Database connect fault: {}", e);
+                None
+            }
+        }
+    }
+}
+
+async fn message_handler(event: &MessageEvent, pool: &PgPool) {
     let msg_to_sg = |msg: Message| match msg {
         Message::Text { text } => MsgSegment {
             r#type: "text".to_string(),
@@ -130,9 +181,7 @@ async fn message_handler(event: &MessageEvent, pool: &sqlx::PgPool) {
                 msg,
                 raw_msg: p.raw_message,
             };
-            if let Err(e) = insert_msg_rev(pool, builder).await {
-                event!(Level::ERROR, "Insert error:\n{:#?}", e)
-            }
+            insert(pool, builder).await;
         }
         MessageEvent::Group(g) => {
             let g = g.clone();
@@ -156,25 +205,57 @@ async fn message_handler(event: &MessageEvent, pool: &sqlx::PgPool) {
                 msg,
                 raw_msg: g.raw_message,
             };
-            if let Err(e) = insert_msg_rev(pool, builder).await {
-                event!(Level::ERROR, "Insert error:\n{:#?}", e)
-            }
+            insert(pool, builder).await;
         }
     }
 }
 
+/// 写入消息并记录耗时
+async fn insert(pool: &PgPool, builder: MsgBuilder) {
+    static INSERT_DURATION: OnceLock<HistogramVec> = OnceLock::new();
+    let histogram = INSERT_DURATION.get_or_init(|| {
+        register_histogram_vec!(
+            "ame_msg_saver_insert_duration_seconds",
+            "MsgSaver database insert latency",
+            &["status"]
+        )
+        .unwrap()
+    });
+    let start = Instant::now();
+    let result = insert_msg_rev(pool, builder).await;
+    let status = if result.is_ok() { "ok" } else { "error" };
+    histogram
+        .with_label_values(&[status])
+        .observe(start.elapsed().as_secs_f64());
+    if let Err(e) = result {
+        event!(Level::ERROR, "Insert error:\n{:#?}", e)
+    }
+}
+
+#[nonebot_rs::plugin(id = "318f9313-a605-498b-9ee3-b5c63f059a24", desc = "Message saver")]
+#[nonebot_rs::async_trait]
 impl nonebot_rs::Plugin for MsgSaver {
+    type Config = MsgSaverConfig;
+
     fn load(&self, event_receiver: EventReceiver, _bot_getter: BotGetter) -> JoinHandle<()> {
         tokio::spawn(self.clone().event_recv(event_receiver))
     }
 
-    fn plugin_info(&self) -> nonebot_rs::plugin::PluginInfo {
-        nonebot_rs::plugin::PluginInfo {
-            name: PLUGIN_NAME,
-            author: PLUGIN_AUTHER,
-            version: PLUGIN_VERSION,
-            desc: PLUGIN_DESC,
-            id: PLUGIN_ID,
+    async fn shutdown(&self) {
+        self.closing.store(true, Ordering::Release);
+        // Shutdown 事件前的消息均已写入
+        let _ = self.running.lock().await;
+        event!(Level::INFO, "MsgSaver flushed.");
+        if let Some(pool) = self.state.get::<PgPool>() {
+            pool.close().await;
         }
     }
+
+    fn set_config(&mut self, config: MsgSaverConfig) {
+        self.config = config;
+    }
+
+    fn set_state(&mut self, state: StateMap) {
+        self.state = state;
+    }
 }
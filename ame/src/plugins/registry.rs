@@ -0,0 +1,133 @@
+use nonebot_rs::{config::NoneBotConfig, plugin::PluginInfo, Nonebot, Plugin};
+use std::collections::HashMap;
+
+/// 已注册的 Plugin
+#[derive(Clone)]
+pub struct Entry {
+    pub info: PluginInfo,
+    pub enabled: bool,
+    /// 默认配置，无配置的 Plugin 为 None
+    pub default_config: Option<toml::Value>,
+    /// `[plugins.<name>]` 解析错误
+    pub error: Option<String>,
+}
+
+/// 注册 ame 的所有 Plugin
+///
+/// Plugin 是否启用依次由命令行覆盖、配置文件 `[plugins.<name>] enabled` 决定，默认启用。
+pub struct Registry<'a> {
+    config: NoneBotConfig,
+    overrides: HashMap<String, bool>,
+    nonebot: Option<&'a mut Nonebot>,
+    entries: Vec<Entry>,
+}
+
+impl<'a> Registry<'a> {
+    /// 仅检查 Plugin 与配置，不添加至 Nonebot
+    pub fn inspect(config: NoneBotConfig) -> Self {
+        Registry {
+            config,
+            overrides: HashMap::new(),
+            nonebot: None,
+            entries: vec![],
+        }
+    }
+
+    /// 将启用的 Plugin 添加至 Nonebot
+    pub fn run(nonebot: &'a mut Nonebot) -> Self {
+        Registry {
+            config: nonebot.config.clone(),
+            overrides: HashMap::new(),
+            nonebot: Some(nonebot),
+            entries: vec![],
+        }
+    }
+
+    /// 覆盖 Plugin 启用状态，名称不区分大小写
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> &mut Self {
+        self.overrides.insert(name.to_ascii_lowercase(), enabled);
+        self
+    }
+
+    pub fn add<P>(&mut self, plugin: P) -> &mut Self
+    where
+        P: Plugin + Send + Sync + 'static,
+    {
+        let info = plugin.plugin_info();
+        let section = self.config.plugin_section(info.name);
+        let enabled = self
+            .overrides
+            .get(&info.name.to_ascii_lowercase())
+            .copied()
+            .or_else(|| section?.get("enabled")?.as_bool())
+            .unwrap_or(true);
+        let default_config = toml::Value::try_from(P::Config::default())
+            .ok()
+            .filter(toml::Value::is_table);
+        let error = match (&default_config, section) {
+            (Some(_), Some(section)) => section.clone().try_into::<P::Config>().err(),
+            _ => None,
+        }
+        .map(|e| e.to_string());
+        if enabled {
+            if let Some(nonebot) = &mut self.nonebot {
+                nonebot.add_plugin(plugin);
+            }
+        }
+        self.entries.push(Entry {
+            info,
+            enabled,
+            default_config,
+            error,
+        });
+        self
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// 未匹配任何已注册 Plugin 的覆盖名称，需在 `register` 后调用
+    pub fn unknown_overrides(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .overrides
+            .keys()
+            .filter(|name| {
+                !self
+                    .entries
+                    .iter()
+                    .any(|entry| entry.info.name.eq_ignore_ascii_case(name))
+            })
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// 注册所有已编译的 Plugin，可选 Plugin 由 cargo feature 控制
+pub fn register(registry: &mut Registry) {
+    let mut matchers = nonebot_rs::Matchers::new_empty();
+    matchers
+        .add_message_matcher(nonebot_rs::builtin::bot_status::bot_status())
+        .add_message_matcher(nonebot_rs::builtin::switch::matcher_switch())
+        .add_message_matchers(nonebot_rs::builtin::admin::admin());
+    #[cfg(feature = "lolicon")]
+    matchers.add_message_matcher(crate::matchers::lolicon::lolicon());
+    registry.add(nonebot_rs::Logger).add(matchers);
+    #[cfg(feature = "moli")]
+    registry.add(super::moli::Moli::new());
+    #[cfg(feature = "msg-saver")]
+    registry.add(super::msg_saver::MsgSaver::default());
+}
+
+#[test]
+fn unknown_overrides_test() {
+    let mut registry = Registry::inspect(NoneBotConfig::default());
+    registry
+        .set_enabled("logger", false)
+        .set_enabled("Nope", true);
+    register(&mut registry);
+    assert_eq!(registry.unknown_overrides(), ["nope"]);
+    assert!(!registry.entries()[0].enabled);
+}
@@ -3,16 +3,17 @@ use serde::{Deserialize, Serialize};
 
 static API_URL: &str = "https://api.mlyai.com/reply";
 
+#[derive(Debug, Clone)]
 pub struct Moli {
-    api_key: &'static str,
-    api_secret: &'static str,
+    api_key: String,
+    api_secret: String,
 }
 
 impl Moli {
-    pub const fn new(api_key: &'static str, api_secret: &'static str) -> Self {
+    pub fn new(api_key: impl Into<String>, api_secret: impl Into<String>) -> Self {
         Self {
-            api_key,
-            api_secret,
+            api_key: api_key.into(),
+            api_secret: api_secret.into(),
         }
     }
 
@@ -98,9 +99,9 @@ pub struct MoliMessageData {
 }
 
 #[tokio::test]
+#[ignore = "requires network"]
 async fn test_without_apikey() {
-    // static MOLI: Moli = Moli::new("ulpxdy2moi9ya4fm", "92kqb4ye");
-    static MOLI: Moli = Moli::new("", "");
+    let moli = Moli::new("", "");
     let msg = MoliReqestParameter::new(
         "呜呜……".to_string(),
         1,
@@ -109,6 +110,6 @@ async fn test_without_apikey() {
         "".to_string(),
         "".to_string(),
     );
-    let res = MOLI.get_response(msg).await;
+    let res = moli.get_response(msg).await;
     println!("{:#?}", res);
 }
@@ -0,0 +1,218 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Ident, ItemFn, LitBool, LitInt, LitStr, Token};
+
+struct CommandArgs {
+    commands: Vec<LitStr>,
+    name: Option<LitStr>,
+    permissions: Vec<Ident>,
+    cooldown: Option<LitInt>,
+    priority: Option<LitInt>,
+    stop_on_error: Option<LitBool>,
+    option_command_start: bool,
+}
+
+const PERMISSIONS: &[&str] = &["superuser", "group_admin", "group", "private"];
+
+impl Parse for CommandArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = CommandArgs {
+            commands: vec![input.parse()?],
+            name: None,
+            permissions: vec![],
+            cooldown: None,
+            priority: None,
+            stop_on_error: None,
+            option_command_start: false,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "aliases" => {
+                    let content;
+                    bracketed!(content in input);
+                    let aliases = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                    args.commands.extend(aliases);
+                }
+                "name" => args.name = Some(input.parse()?),
+                "permission" => {
+                    args.permissions = if input.peek(syn::token::Bracket) {
+                        let content;
+                        bracketed!(content in input);
+                        Punctuated::<Ident, Token![,]>::parse_terminated(&content)?
+                            .into_iter()
+                            .collect()
+                    } else {
+                        vec![input.parse()?]
+                    };
+                    for permission in &args.permissions {
+                        if !PERMISSIONS.contains(&permission.to_string().as_str()) {
+                            return Err(syn::Error::new_spanned(
+                                permission,
+                                "expected `superuser`, `group_admin`, `group` or `private`",
+                            ));
+                        }
+                    }
+                }
+                "cooldown" => args.cooldown = Some(input.parse()?),
+                "priority" => args.priority = Some(input.parse()?),
+                "stop_on_error" => args.stop_on_error = Some(input.parse()?),
+                "option_command_start" => {
+                    args.option_command_start = input.parse::<LitBool>()?.value;
+                }
+                _ => return Err(syn::Error::new_spanned(key, "unknown command argument")),
+            }
+        }
+        Ok(args)
+    }
+}
+
+pub(crate) fn expand(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let args: CommandArgs = syn::parse2(attr)?;
+    let func: ItemFn = syn::parse2(item)?;
+    if func.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            func.sig.fn_token,
+            "#[command] handler must be an async fn",
+        ));
+    }
+    if !func.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &func.sig.generics,
+            "#[command] handler can not be generic",
+        ));
+    }
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = func;
+    let ident = &sig.ident;
+    let handler_ident = format_ident!("{}", camel_case(&ident.to_string()));
+    let name = args
+        .name
+        .unwrap_or_else(|| LitStr::new(&handler_ident.to_string(), Span::call_site()));
+
+    // 长命令优先匹配，避免 `loli` 截断 `lolicon`
+    let mut commands = args.commands;
+    commands.sort_by_key(|c| std::cmp::Reverse(c.value().len()));
+
+    let cooldown = args
+        .cooldown
+        .map(|c| quote!(#c))
+        .unwrap_or_else(|| quote!(0));
+    let command_start = if args.option_command_start {
+        quote!(option_command_start)
+    } else {
+        quote!(command_start)
+    };
+    let permission = if args.permissions.is_empty() {
+        quote!()
+    } else {
+        let rules = args
+            .permissions
+            .iter()
+            .map(|p| match p.to_string().as_str() {
+                "superuser" => quote!(::nonebot_rs::builtin::rules::is_superuser()),
+                "group_admin" => quote!(::nonebot_rs::builtin::rules::is_group_admin()),
+                "group" => quote!(::nonebot_rs::builtin::rules::is_group_message_event()),
+                _ => quote!(::nonebot_rs::builtin::rules::is_private_message_event()),
+            });
+        quote! {
+            matcher.add_rule(::nonebot_rs::builtin::rules::any_of(vec![#(#rules),*]));
+        }
+    };
+    let priority = args
+        .priority
+        .map(|p| quote!(matcher.set_priority(#p);))
+        .unwrap_or_default();
+    let stop_on_error = args
+        .stop_on_error
+        .map(|b| quote!(matcher.set_stop_on_error(#b);))
+        .unwrap_or_default();
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #vis struct #handler_ident {
+            cooldown: ::nonebot_rs::builtin::cooldown::Cooldown,
+        }
+
+        impl #handler_ident {
+            #sig #block
+        }
+
+        #[::nonebot_rs::async_trait]
+        impl ::nonebot_rs::builtin::matcher::Handler<::nonebot_rs::event::MessageEvent>
+            for #handler_ident
+        {
+            fn match_(&self, event: &mut ::nonebot_rs::event::MessageEvent) -> bool {
+                for command in [#(#commands),*] {
+                    if let Some(raw_message) = event.get_raw_message().strip_prefix(command) {
+                        let raw_message = raw_message.to_string();
+                        event.set_raw_message(raw_message);
+                        return true;
+                    }
+                }
+                false
+            }
+
+            async fn handle(
+                &self,
+                event: ::nonebot_rs::event::MessageEvent,
+                matcher: ::nonebot_rs::builtin::matcher::Matcher<::nonebot_rs::event::MessageEvent>,
+            ) -> ::nonebot_rs::builtin::matcher::HandlerResult {
+                let Some(fut) = ::nonebot_rs::builtin::matcher::extract::HandlerFn::call(
+                    &Self::#ident,
+                    &event,
+                    &matcher,
+                ) else {
+                    return Ok(::nonebot_rs::builtin::matcher::Flow::Continue);
+                };
+                if !self
+                    .cooldown
+                    .check(&::nonebot_rs::event::UserId::get_user_id(&event))
+                {
+                    return Ok(::nonebot_rs::builtin::matcher::Flow::Stop);
+                }
+                fut.await
+            }
+        }
+
+        #(#attrs)*
+        #vis fn #ident() -> ::nonebot_rs::builtin::matcher::Matcher<::nonebot_rs::event::MessageEvent> {
+            let mut matcher = ::nonebot_rs::builtin::matcher::Matcher::new(
+                #name,
+                #handler_ident {
+                    cooldown: ::nonebot_rs::builtin::cooldown::Cooldown::new(#cooldown),
+                },
+            );
+            matcher.add_pre_matcher(::nonebot_rs::builtin::prematchers::#command_start());
+            #permission
+            #priority
+            #stop_on_error
+            matcher
+        }
+    })
+}
+
+fn camel_case(s: &str) -> String {
+    s.split('_')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,54 @@
+//! nonebot-rs 属性宏，通过 `nonebot_rs::command` 与 `nonebot_rs::plugin` 使用
+
+use proc_macro::TokenStream;
+
+mod command;
+mod plugin;
+
+/// 将 async handler 函数定义为命令 Matcher
+///
+/// 函数参数需实现 `FromEvent`，宏会生成同名驼峰命名的 Handler 结构体，
+/// 并将原函数替换为返回 `Matcher<MessageEvent>` 的同名构造函数。
+///
+/// ```ignore
+/// #[command("lolicon", aliases = ["loli", "色图"], permission = group, cooldown = 10)]
+/// async fn lolicon(reply: Reply) {
+///     reply.text("正在装填弹药...").await;
+/// }
+///
+/// matchers.add_message_matcher(lolicon());
+/// ```
+///
+/// 可选参数：
+/// - `aliases`：命令别名
+/// - `name`：Matcher 名称，默认为函数名驼峰形式
+/// - `permission`：`superuser` | `group_admin` | `group` | `private`，可为数组
+/// - `cooldown`：同一用户触发间隔（秒）
+/// - `priority`：Matcher 优先级
+/// - `stop_on_error`：Handler 返回错误或 panic 时是否阻止事件传递
+/// - `option_command_start`：为 true 时命令不强制以 `command_start` 开头
+#[proc_macro_attribute]
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    command::expand(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// 为 `impl Plugin for T` 生成 `plugin_info`，未声明 `type Config` 时生成 `type Config = ();`
+///
+/// ```ignore
+/// #[plugin(id = "318f9313-a605-498b-9ee3-b5c63f059a24", desc = "Message saver")]
+/// impl Plugin for MsgSaver {
+///     fn load(&self, event_receiver: EventReceiver, bot_getter: BotGetter) -> JoinHandle<()> {
+///         ...
+///     }
+/// }
+/// ```
+///
+/// `name` 默认为类型名，`author`、`version`、`desc` 默认取自所在 crate 的 Cargo.toml
+#[proc_macro_attribute]
+pub fn plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    plugin::expand(attr.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
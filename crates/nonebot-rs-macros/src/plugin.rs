@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+use syn::{ImplItem, ItemImpl, LitStr, Type};
+
+#[derive(Default)]
+struct PluginArgs {
+    id: Option<LitStr>,
+    name: Option<LitStr>,
+    author: Option<LitStr>,
+    version: Option<LitStr>,
+    desc: Option<LitStr>,
+}
+
+pub(crate) fn expand(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let mut args = PluginArgs::default();
+    let parser = syn::meta::parser(|meta| {
+        let slot = if meta.path.is_ident("id") {
+            &mut args.id
+        } else if meta.path.is_ident("name") {
+            &mut args.name
+        } else if meta.path.is_ident("author") {
+            &mut args.author
+        } else if meta.path.is_ident("version") {
+            &mut args.version
+        } else if meta.path.is_ident("desc") {
+            &mut args.desc
+        } else {
+            return Err(meta.error("expected `id`, `name`, `author`, `version` or `desc`"));
+        };
+        *slot = Some(meta.value()?.parse()?);
+        Ok(())
+    });
+    parser.parse2(attr)?;
+
+    let mut item: ItemImpl = syn::parse2(item)?;
+    if item.trait_.is_none() {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "#[plugin] must be used on `impl Plugin for T`",
+        ));
+    }
+    let mut has_config = false;
+    for impl_item in &item.items {
+        match impl_item {
+            ImplItem::Fn(f) if f.sig.ident == "plugin_info" => {
+                return Err(syn::Error::new_spanned(
+                    &f.sig.ident,
+                    "`plugin_info` is generated by #[plugin]",
+                ));
+            }
+            ImplItem::Type(t) if t.ident == "Config" => has_config = true,
+            _ => {}
+        }
+    }
+
+    let Some(id) = args.id else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "missing `id = \"<uuid>\"`",
+        ));
+    };
+    let id = parse_uuid(&id)?;
+    let name = match args.name {
+        Some(name) => quote!(#name),
+        None => {
+            let name = type_name(&item.self_ty)?;
+            quote!(#name)
+        }
+    };
+    let author = args
+        .author
+        .map(|a| quote!(#a))
+        .unwrap_or_else(|| quote!(env!("CARGO_PKG_AUTHORS")));
+    let version = args
+        .version
+        .map(|v| quote!(#v))
+        .unwrap_or_else(|| quote!(env!("CARGO_PKG_VERSION")));
+    let desc = args
+        .desc
+        .map(|d| quote!(#d))
+        .unwrap_or_else(|| quote!(env!("CARGO_PKG_DESCRIPTION")));
+
+    if !has_config {
+        item.items.push(syn::parse2(quote! {
+            type Config = ();
+        })?);
+    }
+    item.items.push(syn::parse2(quote! {
+        fn plugin_info(&self) -> ::nonebot_rs::plugin::PluginInfo {
+            ::nonebot_rs::plugin::PluginInfo {
+                name: #name,
+                author: #author,
+                version: #version,
+                desc: #desc,
+                id: ::nonebot_rs::plugin::prelude::Uuid::from_u128(#id),
+            }
+        }
+    })?);
+    Ok(quote!(#item))
+}
+
+/// 编译期校验 uuid 并转为 u128 字面量
+fn parse_uuid(lit: &LitStr) -> syn::Result<proc_macro2::Literal> {
+    let hex: String = lit.value().chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        return Err(syn::Error::new_spanned(lit, "invalid uuid"));
+    }
+    u128::from_str_radix(&hex, 16)
+        .map(proc_macro2::Literal::u128_suffixed)
+        .map_err(|_| syn::Error::new_spanned(lit, "invalid uuid"))
+}
+
+fn type_name(ty: &Type) -> syn::Result<String> {
+    match ty {
+        Type::Path(p) => match p.path.segments.last() {
+            Some(segment) => Ok(segment.ident.to_string()),
+            None => Err(syn::Error::new_spanned(ty, "expected a named type")),
+        },
+        _ => Err(syn::Error::new_spanned(ty, "expected a named type")),
+    }
+}
@@ -1,10 +1,13 @@
-use crate::{ApiChannelItem, Nonebot};
-use colored::*;
+use crate::event::{Event, NoneBotEvent};
+use crate::{ApiChannelItem, Nonebot, Plugin};
 use tokio::sync::{mpsc, watch};
 use tracing::{event, Level};
+use uuid::Uuid;
 
 /// Nonebot 内部设置项
-#[derive(Debug, Clone)]
+///
+/// 通过 `Nonebot::action_sender` 或 `Bot.action_sender` 发送，运行时变更 Bot 与 Plugin
+#[derive(Debug)]
 pub enum Action {
     /// 添加 Bot
     AddBot {
@@ -20,12 +23,38 @@ pub enum Action {
         bot_id: String,
         bot_config: crate::config::BotConfig,
     },
+    /// 重新读取配置文件并更新所有 Bot 的 BotConfig，连接设置需重启生效
+    ReloadConfig,
+    /// 添加并启动 Plugin，不会读取 `[plugins.<name>]`，需在发送前调用 `set_config`
+    AddPlugin {
+        plugin: Box<dyn Plugin + Send + Sync>,
+    },
+    /// 关闭并移除 Plugin
+    RemovePlugin { id: Uuid },
+    /// 设置 Matcher 开关，group_id 为 None 时设置默认开关
+    DisableMatcher {
+        name: String,
+        group_id: Option<String>,
+        disable: bool,
+    },
+    /// 关闭 Nonebot
+    Shutdown,
 }
 
 impl Nonebot {
-    /// 处理 Nonebot 内部 Action
+    /// 持续处理 Nonebot 内部 Action，收到 `Action::Shutdown` 时返回
     pub async fn handle_action(&mut self) {
-        if let Some(action) = self.action_receiver.recv().await {
+        loop {
+            let action = tokio::select! {
+                action = self.action_receiver.recv() => action,
+                Some(exit) = self.supervisor.join_next() => {
+                    self.handle_plugin_exit(exit);
+                    continue;
+                }
+            };
+            let Some(action) = action else {
+                return;
+            };
             event!(Level::DEBUG, "Receive Action {:?}", action);
             match action {
                 Action::AddBot {
@@ -41,36 +70,67 @@ impl Nonebot {
                         api_resp_watcher.clone(),
                     );
                     self.event_sender
-                        .send(crate::event::Event::Nonebot(
-                            crate::event::NoneBotEvent::BotConnect { bot },
-                        ))
-                        .unwrap();
-                    event!(Level::DEBUG, "Add Bot [{}]", bot_id);
+                        .send(Event::Nonebot(NoneBotEvent::BotConnect { bot }))
+                        .ok();
+                    event!(Level::DEBUG, bot_id = %bot_id, "Add Bot");
                 }
                 Action::RemoveBot { bot_id } => {
                     let bot = self.remove_bot(bot_id.clone());
                     match bot {
                         Some(bot) => {
-                            event!(Level::DEBUG, "Remove Bot [{}]", bot.bot_id.bright_red());
+                            event!(Level::DEBUG, bot_id = %bot.bot_id, "Remove Bot");
                             self.event_sender
-                                .send(crate::event::Event::Nonebot(
-                                    crate::event::NoneBotEvent::BotDisconnect { bot },
-                                ))
-                                .unwrap();
+                                .send(Event::Nonebot(NoneBotEvent::BotDisconnect { bot }))
+                                .ok();
                         }
                         None => {
-                            event!(
-                                Level::WARN,
-                                "Removing not exists Bot [{}]",
-                                bot_id.bright_red()
-                            );
+                            event!(Level::WARN, bot_id = %bot_id, "Removing not exists Bot");
                         }
                     }
                 }
                 Action::ChangeBotConfig { bot_id, bot_config } => {
-                    let bot = self.bots.get_mut(&bot_id).unwrap();
-                    bot.config = bot_config;
+                    match self.bots.get_mut(&bot_id) {
+                        Some(bot) => {
+                            bot.config = bot_config;
+                            self.bot_sender.send(self.bots.clone()).ok();
+                        }
+                        None => event!(
+                            Level::WARN,
+                            bot_id = %bot_id,
+                            "Changing config of not exists Bot"
+                        ),
+                    }
+                }
+                Action::ReloadConfig => self.reload_config(),
+                Action::AddPlugin { plugin } => {
+                    let id = plugin.plugin_info().id;
+                    if self.plugins.contains_key(&id) {
+                        event!(
+                            Level::WARN,
+                            plugin = plugin.plugin_info().name,
+                            plugin_id = %id,
+                            "Plugin already exists."
+                        );
+                        continue;
+                    }
+                    self.insert_plugin(plugin);
+                    self.load_plugin(&id);
+                }
+                Action::RemovePlugin { id } => self.unload_plugin(&id),
+                Action::DisableMatcher {
+                    name,
+                    group_id,
+                    disable,
+                } => {
+                    self.event_sender
+                        .send(Event::Nonebot(NoneBotEvent::DisableMatcher {
+                            name,
+                            group_id,
+                            disable,
+                        }))
+                        .ok();
                 }
+                Action::Shutdown => return,
             }
         }
     }
@@ -0,0 +1,367 @@
+//! 管理 HTTP Api
+//!
+//! 配置 `[admin]` 后在 `http://<host>:<port>` 提供运行时管理接口，所有请求需携带
+//! `Authorization: Bearer <access_token>`，错误以 `{"error": "..."}` 返回：
+//!
+//! - `GET /bots` 已连接的 Bot 及连接时间
+//! - `GET /plugins` Plugin 信息及健康状态
+//! - `GET /matchers` Matcher 优先级、stop_on_error、disable 及分群开关
+//! - `POST /matchers/enable`、`POST /matchers/disable` 设置 Matcher 开关，
+//!   `{"name": "...", "group_id": "..."}`，省略 group_id 时设置默认开关
+//! - `POST /bots/<bot_id>/send` 发送消息，`{"group_id" | "user_id": "...", "message": "..." | [Message]}`
+//! - `POST /bots/<bot_id>/api` 调用 Onebot Api 并返回 ApiResp，echo 可省略
+//!
+//! 所有变更操作均输出 INFO 日志。
+
+use crate::api::Api;
+use crate::builtin::matcher::matchers::MatcherTable;
+use crate::config::AdminConfig;
+use crate::http::{json, HttpResponse};
+use crate::message::Message;
+use crate::plugin::PluginInfo;
+use crate::secret::Secret;
+use crate::shutdown::ShutdownWatcher;
+use crate::state::StateMap;
+use crate::supervisor::{PluginHealth, PluginHealthTable};
+use crate::{Action, ActionSender, Bot, BotGetter};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use hyper::body::Incoming;
+use hyper::header::AUTHORIZATION;
+use hyper::{Method, Request, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{event, Level};
+
+/// 请求体最大字节数
+const MAX_BODY_SIZE: usize = 64 * 1024;
+/// 等待 Onebot 返回项的时间，`call_api_resp` 仅在收到其他返回项时检查超时
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 管理 Api 所需的 Nonebot 组件
+struct Admin {
+    access_token: Secret,
+    state: StateMap,
+    bot_getter: BotGetter,
+    action_sender: ActionSender,
+}
+
+#[derive(Serialize)]
+struct BotInfo {
+    bot_id: String,
+    connect_time: i64,
+}
+
+#[derive(Serialize)]
+struct PluginEntry {
+    info: PluginInfo,
+    health: PluginHealth,
+}
+
+#[derive(Deserialize)]
+struct SwitchMatcher {
+    name: String,
+    #[serde(default, deserialize_with = "crate::utils::option_id_deserializer")]
+    group_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SendMessage {
+    #[serde(default, deserialize_with = "crate::utils::option_id_deserializer")]
+    group_id: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::option_id_deserializer")]
+    user_id: Option<String>,
+    message: MessageBody,
+}
+
+/// 纯文本或 Onebot 消息段
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MessageBody {
+    Text(String),
+    Messages(Vec<Message>),
+}
+
+/// 启动管理 HTTP Api，进入 `Closing` 阶段后返回
+pub async fn serve(
+    config: AdminConfig,
+    state: StateMap,
+    bot_getter: BotGetter,
+    action_sender: ActionSender,
+    shutdown: ShutdownWatcher,
+) {
+    let Some(listener) = crate::http::bind("admin", config.host, config.port).await else {
+        return;
+    };
+    let admin = Admin {
+        access_token: config.access_token,
+        state,
+        bot_getter,
+        action_sender,
+    };
+    serve_listener(listener, admin, shutdown).await
+}
+
+async fn serve_listener(listener: TcpListener, admin: Admin, shutdown: ShutdownWatcher) {
+    let admin = Arc::new(admin);
+    crate::http::serve("admin", listener, shutdown, move |req| {
+        admin.clone().handle(req)
+    })
+    .await
+}
+
+/// 以 `{"error": "..."}` 返回的错误
+struct ApiError(StatusCode, String);
+
+fn error(status: StatusCode, message: impl std::fmt::Display) -> ApiError {
+    ApiError(status, message.to_string())
+}
+
+impl ApiError {
+    fn into_response(self) -> HttpResponse {
+        json(self.0, &serde_json::json!({ "error": self.1 }))
+    }
+}
+
+/// 读取并解析 JSON 请求体
+async fn read_json<T: DeserializeOwned>(req: Request<Incoming>) -> Result<T, ApiError> {
+    let body = match Limited::new(req.into_body(), MAX_BODY_SIZE).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) if e.is::<LengthLimitError>() => {
+            return Err(error(StatusCode::PAYLOAD_TOO_LARGE, e));
+        }
+        Err(e) => return Err(error(StatusCode::BAD_REQUEST, e)),
+    };
+    serde_json::from_slice(&body).map_err(|e| error(StatusCode::BAD_REQUEST, e))
+}
+
+impl Admin {
+    async fn handle(self: Arc<Self>, req: Request<Incoming>) -> HttpResponse {
+        self.route(req)
+            .await
+            .unwrap_or_else(ApiError::into_response)
+    }
+
+    async fn route(&self, req: Request<Incoming>) -> Result<HttpResponse, ApiError> {
+        let authorized = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| self.access_token.matches(token));
+        if !authorized {
+            return Err(error(StatusCode::UNAUTHORIZED, "invalid access token"));
+        }
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            (Method::GET, ["bots"]) => Ok(self.bots()),
+            (Method::GET, ["plugins"]) => Ok(self.plugins()),
+            (Method::GET, ["matchers"]) => Ok(self.matchers()),
+            (Method::POST, ["matchers", op @ ("enable" | "disable")]) => {
+                let disable = *op == "disable";
+                self.switch_matcher(read_json(req).await?, disable).await
+            }
+            (Method::POST, ["bots", bot_id, "send"]) => {
+                self.send(bot_id, read_json(req).await?).await
+            }
+            (Method::POST, ["bots", bot_id, "api"]) => {
+                self.call_api(bot_id, read_json(req).await?).await
+            }
+            _ => Err(error(StatusCode::NOT_FOUND, "not found")),
+        }
+    }
+
+    fn bot(&self, bot_id: &str) -> Result<Bot, ApiError> {
+        self.bot_getter
+            .borrow()
+            .get(bot_id)
+            .cloned()
+            .ok_or_else(|| error(StatusCode::NOT_FOUND, format!("bot {} not found", bot_id)))
+    }
+
+    fn bots(&self) -> HttpResponse {
+        let mut bots: Vec<BotInfo> = self
+            .bot_getter
+            .borrow()
+            .values()
+            .map(|bot| BotInfo {
+                bot_id: bot.bot_id.clone(),
+                connect_time: bot.connect_time,
+            })
+            .collect();
+        bots.sort_by(|a, b| a.bot_id.cmp(&b.bot_id));
+        json(StatusCode::OK, &bots)
+    }
+
+    fn plugins(&self) -> HttpResponse {
+        let mut plugins: Vec<PluginEntry> = self
+            .state
+            .get::<PluginHealthTable>()
+            .map(|table| table.plugins())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(info, health)| PluginEntry { info, health })
+            .collect();
+        plugins.sort_by(|a, b| a.info.name.cmp(b.info.name));
+        json(StatusCode::OK, &plugins)
+    }
+
+    fn matchers(&self) -> HttpResponse {
+        let matchers = self
+            .state
+            .get::<MatcherTable>()
+            .map(|table| table.all())
+            .unwrap_or_default();
+        json(StatusCode::OK, &matchers)
+    }
+
+    async fn switch_matcher(
+        &self,
+        body: SwitchMatcher,
+        disable: bool,
+    ) -> Result<HttpResponse, ApiError> {
+        let exists = self
+            .state
+            .get::<MatcherTable>()
+            .is_some_and(|table| table.get(&body.name).is_some());
+        if !exists {
+            return Err(error(
+                StatusCode::NOT_FOUND,
+                format!("matcher {} not found", body.name),
+            ));
+        }
+        event!(
+            Level::INFO,
+            matcher = %body.name,
+            group_id = ?body.group_id,
+            disable,
+            "Admin Api set matcher switch"
+        );
+        let response = serde_json::json!({
+            "name": body.name,
+            "group_id": body.group_id,
+            "disable": disable,
+        });
+        self.action_sender
+            .send(Action::DisableMatcher {
+                name: body.name,
+                group_id: body.group_id,
+                disable,
+            })
+            .await
+            .map_err(|e| error(StatusCode::SERVICE_UNAVAILABLE, e))?;
+        Ok(json(StatusCode::ACCEPTED, &response))
+    }
+
+    async fn send(&self, bot_id: &str, body: SendMessage) -> Result<HttpResponse, ApiError> {
+        let bot = self.bot(bot_id)?;
+        let message = match body.message {
+            MessageBody::Text(text) => vec![Message::text(text)],
+            MessageBody::Messages(messages) => messages,
+        };
+        match (body.group_id, body.user_id) {
+            (Some(group_id), None) => {
+                event!(Level::INFO, bot_id, group_id, "Admin Api send message");
+                bot.send_group_msg(&group_id, message).await;
+            }
+            (None, Some(user_id)) => {
+                event!(Level::INFO, bot_id, user_id, "Admin Api send message");
+                bot.send_private_msg(&user_id, message).await;
+            }
+            _ => {
+                return Err(error(
+                    StatusCode::BAD_REQUEST,
+                    "exactly one of group_id and user_id is required",
+                ))
+            }
+        }
+        Ok(json(StatusCode::ACCEPTED, &serde_json::json!({})))
+    }
+
+    async fn call_api(
+        &self,
+        bot_id: &str,
+        mut body: serde_json::Value,
+    ) -> Result<HttpResponse, ApiError> {
+        let bot = self.bot(bot_id)?;
+        if let Some(object) = body.as_object_mut() {
+            object
+                .entry("echo")
+                .or_insert_with(|| format!("admin-{}", uuid::Uuid::new_v4()).into());
+        }
+        let api: Api =
+            serde_json::from_value(body).map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+        event!(
+            Level::INFO,
+            bot_id,
+            action = %api.action(),
+            "Admin Api call Api"
+        );
+        match tokio::time::timeout(API_TIMEOUT, bot.call_api_resp(api)).await {
+            Ok(Some(resp)) => Ok(json(StatusCode::OK, &resp)),
+            Ok(None) | Err(_) => Err(error(StatusCode::GATEWAY_TIMEOUT, "api call timeout")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn admin_test() {
+    use crate::builtin::matcher::matchers::MatcherInfo;
+    use crate::http::test_request;
+    use crate::shutdown::ShutdownPhase;
+
+    let state = StateMap::new();
+    let table = MatcherTable::default();
+    table.publish(
+        uuid::Uuid::new_v4(),
+        vec![MatcherInfo {
+            name: "echo".to_string(),
+            event: "message",
+            priority: 1,
+            stop_on_error: true,
+            disable: false,
+            switch: Default::default(),
+        }],
+        Default::default(),
+    );
+    state.insert(table);
+    let (_bot_sender, bot_getter) = tokio::sync::watch::channel(Default::default());
+    let (action_sender, mut action_receiver) = tokio::sync::mpsc::channel(4);
+    let (shutdown_sender, shutdown) = tokio::sync::watch::channel(ShutdownPhase::Running);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let admin = Admin {
+        access_token: Secret::new("token"),
+        state,
+        bot_getter,
+        action_sender,
+    };
+    let server = tokio::spawn(serve_listener(listener, admin, shutdown));
+
+    let response = test_request(port, "GET", "/matchers", &[], "").await;
+    assert!(response.starts_with("HTTP/1.1 401"));
+    let auth = [("Authorization", "Bearer token")];
+    let response = test_request(port, "GET", "/matchers", &auth, "").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains(r#""name":"echo""#));
+    let response = test_request(port, "POST", "/matchers/disable", &auth, r#"{"name":"x"}"#).await;
+    assert!(response.starts_with("HTTP/1.1 404"));
+    let body = r#"{"name":"echo","group_id":123}"#;
+    let response = test_request(port, "POST", "/matchers/disable", &auth, body).await;
+    assert!(response.starts_with("HTTP/1.1 202"));
+    assert!(matches!(
+        action_receiver.recv().await,
+        Some(Action::DisableMatcher { name, group_id: Some(group_id), disable: true })
+            if name == "echo" && group_id == "123"
+    ));
+    let response = test_request(port, "POST", "/bots/1/send", &auth, r#"{"message":"hi"}"#).await;
+    assert!(response.starts_with("HTTP/1.1 404"));
+
+    shutdown_sender.send_replace(ShutdownPhase::Closing);
+    server.await.unwrap();
+}
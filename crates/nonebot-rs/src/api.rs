@@ -125,6 +125,21 @@ macro_rules! echos {
                 } => echo.clone(),)*
             }
         }
+
+        /// Onebot action 名称，如 `send_group_msg`
+        pub fn action(&self) -> String {
+            let name = match self {
+                $(Api::$x { .. } => stringify!($x),)*
+            };
+            let mut action = String::with_capacity(name.len() + 4);
+            for (i, c) in name.chars().enumerate() {
+                if c.is_ascii_uppercase() && i > 0 {
+                    action.push('_');
+                }
+                action.push(c.to_ascii_lowercase());
+            }
+            action
+        }
     };
 }
 
@@ -419,3 +434,20 @@ pub struct GetImage {
 pub struct SetRestart {
     pub delay: i64,
 }
+
+#[test]
+fn action_test() {
+    let apis = [
+        Api::get_csrf_token(),
+        Api::can_send_image(),
+        Api::send_group_msg(SendGroupMsg {
+            group_id: "1".to_string(),
+            message: vec![],
+            auto_escape: false,
+        }),
+    ];
+    for api in apis {
+        let value = serde_json::to_value(&api).unwrap();
+        assert_eq!(value["action"].as_str(), Some(api.action().as_str()));
+    }
+}
@@ -1,12 +1,17 @@
 use crate::api::Api;
 use crate::event::MessageEvent;
 use crate::{config, message, utils, ActionSender, ApiChannelItem, ApiResp};
-use colored::*;
+use std::time::Instant;
 use tokio::sync::{mpsc, watch};
 use tracing::{event, Level};
 
 mod api;
 
+/// 不等待返回的 Api 调用状态
+const API_SENT: &str = "sent";
+/// 未收到返回的 Api 调用状态
+const API_TIMEOUT: &str = "timeout";
+
 /// 为 Plugin 提供各类 Onebot Api
 #[derive(Debug, Clone)]
 pub struct Bot {
@@ -54,12 +59,13 @@ impl Bot {
             )))
             .await
             .unwrap();
+        crate::metrics::metrics().api_called("send_group_msg", API_SENT, None);
         event!(
             Level::INFO,
-            "Bot [{}] Send {:?} to Group ({})",
-            self.config.bot_id.red(),
-            msg,
-            group_id.to_string().magenta()
+            bot_id = %self.bot_id,
+            group_id,
+            "Send {:?}",
+            msg
         );
     }
 
@@ -75,12 +81,13 @@ impl Bot {
             )))
             .await
             .unwrap();
+        crate::metrics::metrics().api_called("send_private_msg", API_SENT, None);
         event!(
             Level::INFO,
-            "Bot [{}] Send {:?} to Friend ({})",
-            self.config.bot_id.red(),
-            msg,
-            user_id.to_string().green()
+            bot_id = %self.bot_id,
+            user_id,
+            "Send {:?}",
+            msg
         );
     }
 
@@ -98,10 +105,13 @@ impl Bot {
             .send(ApiChannelItem::Api(api.clone()))
             .await
             .unwrap();
+        crate::metrics::metrics().api_called(&api.action(), API_SENT, None);
         event!(
             Level::INFO,
-            "Bot [{}] Calling Api {:?}",
-            self.config.bot_id.red(),
+            bot_id = %self.bot_id,
+            action = %api.action(),
+            echo = %api.get_echo(),
+            "Calling Api {:?}",
             api
         );
     }
@@ -115,21 +125,36 @@ impl Bot {
             .unwrap();
         event!(
             Level::INFO,
-            "Bot [{}] Calling Api {:?}",
-            self.config.bot_id.red(),
+            bot_id = %self.bot_id,
+            action = %api.action(),
+            echo,
+            "Calling Api {:?}",
             api
         );
         let time = utils::timestamp();
+        let start = Instant::now();
         let mut watcher = self.api_resp_watcher.clone();
+        let mut resp = None;
         while watcher.changed().await.is_ok() {
-            let resp = self.api_resp_watcher.borrow().clone();
-            if resp.echo == echo {
-                return Some(resp);
+            let r = self.api_resp_watcher.borrow().clone();
+            if r.echo == echo {
+                resp = Some(r);
+                break;
             }
             if utils::timestamp() > time + 30 {
-                return None;
+                break;
             }
         }
-        None
+        let status = resp.as_ref().map_or(API_TIMEOUT, |r| r.status.as_str());
+        event!(
+            Level::DEBUG,
+            bot_id = %self.bot_id,
+            echo,
+            status,
+            "Api responded in {:?}",
+            start.elapsed()
+        );
+        crate::metrics::metrics().api_called(&api.action(), status, Some(start.elapsed()));
+        resp
     }
 }
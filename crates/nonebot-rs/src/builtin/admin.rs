@@ -0,0 +1,418 @@
+use crate::builtin::matcher::matchers::MatcherTable;
+use crate::builtin::matcher::prelude::*;
+use crate::builtin::switch::{self, DEFAULT_SCOPE};
+use crate::supervisor::PluginHealthTable;
+use crate::Action;
+use tracing::{event, Level};
+
+/// 先于 MatcherSwitch 处理 superuser 的 enable/disable
+const ADMIN_PRIORITY: i8 = 0;
+/// 确认回复
+const CONFIRM_REPLIES: [&str; 3] = ["y", "yes", "确认"];
+
+/// 管理命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Plugins,
+    Matchers,
+    Enable,
+    Disable,
+    Broadcast,
+    Reload,
+    Leave,
+    Restart,
+}
+
+impl Command {
+    fn command(&self) -> &'static str {
+        match self {
+            Command::Plugins => "plugins",
+            Command::Matchers => "matchers",
+            Command::Enable => "enable",
+            Command::Disable => "disable",
+            Command::Broadcast => "broadcast",
+            Command::Reload => "reload",
+            Command::Leave => "leave",
+            Command::Restart => "restart",
+        }
+    }
+
+    /// Matcher 名称，均以 `switch::ADMIN_PREFIX` 开头，不可被开关
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Plugins => "AdminPlugins",
+            Command::Matchers => "AdminMatchers",
+            Command::Enable => "AdminEnable",
+            Command::Disable => "AdminDisable",
+            Command::Broadcast => "AdminBroadcast",
+            Command::Reload => "AdminReload",
+            Command::Leave => "AdminLeave",
+            Command::Restart => "AdminRestart",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Admin(Command);
+
+#[async_trait]
+impl Handler<MessageEvent> for Admin {
+    fn match_(&self, event: &mut MessageEvent) -> bool {
+        event.get_raw_message().split_whitespace().next() == Some(self.0.command())
+    }
+
+    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+        let raw_message = event.get_raw_message().to_string();
+        let args: Vec<&str> = raw_message.split_whitespace().skip(1).collect();
+        match self.0 {
+            Command::Plugins => matcher.send_text(&plugins(&matcher)).await,
+            Command::Matchers => matcher.send_text(&matchers(&event, &matcher)).await,
+            Command::Enable => switch(&event, &matcher, &args, true).await,
+            Command::Disable => switch(&event, &matcher, &args, false).await,
+            Command::Broadcast => {
+                let message = raw_message
+                    .trim_start()
+                    .trim_start_matches(self.0.command())
+                    .trim();
+                broadcast(&event, &matcher, message).await
+            }
+            Command::Reload => {
+                if confirm(&matcher, "重新读取配置文件").await {
+                    audit(&event, self.0, "");
+                    matcher.set(Action::ReloadConfig).await;
+                    matcher.send_text("已重新读取配置文件").await;
+                }
+            }
+            Command::Leave => leave(&event, &matcher, &args).await,
+            Command::Restart => {
+                if confirm(&matcher, "重启 Onebot 实现").await {
+                    audit(&event, self.0, "");
+                    matcher.send_text("正在重启 Onebot 实现").await;
+                    matcher.set_restart(0).await;
+                }
+            }
+        }
+        Ok(Flow::Stop)
+    }
+}
+
+/// 请求 superuser 确认，超时或其他回复视为取消
+async fn confirm(matcher: &Matcher<MessageEvent>, action: &str) -> bool {
+    let prompt = format!("确认{}？回复 y 确认", action);
+    match matcher.session().prompt_text(Some(&prompt)).await {
+        Ok(reply) if CONFIRM_REPLIES.contains(&reply.trim().to_lowercase().as_str()) => true,
+        Err(SessionError::Timeout) => {
+            matcher.send_text("确认超时，已取消").await;
+            false
+        }
+        Err(SessionError::Closed) => false,
+        _ => {
+            matcher.send_text("已取消").await;
+            false
+        }
+    }
+}
+
+/// 记录已确认的管理命令
+fn audit(event: &MessageEvent, command: Command, args: &str) {
+    event!(
+        Level::INFO,
+        bot_id = %event.get_self_id(),
+        user_id = %event.get_user_id(),
+        command = command.command(),
+        args,
+        "Admin command confirmed"
+    );
+}
+
+fn plugins(matcher: &Matcher<MessageEvent>) -> String {
+    let Some(table) = matcher.get_state::<PluginHealthTable>() else {
+        return "未找到 Plugin 信息".to_string();
+    };
+    let mut plugins = table.plugins();
+    plugins.sort_by(|a, b| a.0.name.cmp(b.0.name));
+    let mut text = format!("已加载 {} 个 Plugin：", plugins.len());
+    for (info, health) in plugins {
+        text.push_str(&format!(
+            "\n{} {}：{}",
+            info.name, info.version, health.status
+        ));
+        if health.restarts > 0 {
+            text.push_str(&format!("（已重启 {} 次）", health.restarts));
+        }
+    }
+    text
+}
+
+fn matchers(event: &MessageEvent, matcher: &Matcher<MessageEvent>) -> String {
+    let Some(table) = matcher.get_state::<MatcherTable>() else {
+        return "未找到 Matcher 信息".to_string();
+    };
+    let group_id = match event {
+        MessageEvent::Group(g) => Some(g.group_id.as_str()),
+        MessageEvent::Private(_) => None,
+    };
+    let matchers = table.all();
+    let mut text = format!("已加载 {} 个 Matcher：", matchers.len());
+    for info in matchers {
+        let state = if info.disable {
+            "全局禁用"
+        } else if info.switch.is_enabled(group_id) {
+            "启用"
+        } else {
+            "禁用"
+        };
+        text.push_str(&format!(
+            "\n[{}] {} {}：{}",
+            info.priority, info.event, info.name, state
+        ));
+        if info.stop_on_error {
+            text.push_str("（出错时阻断）");
+        }
+    }
+    text
+}
+
+async fn switch(
+    event: &MessageEvent,
+    matcher: &Matcher<MessageEvent>,
+    args: &[&str],
+    enable: bool,
+) {
+    let command = if enable {
+        Command::Enable
+    } else {
+        Command::Disable
+    };
+    let usage = format!("用法：{} <Matcher> [群号|default]", command.command());
+    let Some(&name) = args.first() else {
+        matcher.send_text(&usage).await;
+        return;
+    };
+    let group_id = match (args.get(1), event) {
+        (Some(&DEFAULT_SCOPE), _) => None,
+        (Some(group_id), _) => Some(group_id.to_string()),
+        (None, MessageEvent::Group(g)) => Some(g.group_id.clone()),
+        (None, MessageEvent::Private(_)) => {
+            matcher.send_text(&usage).await;
+            return;
+        }
+    };
+    if let Err(reply) = switch::check_target(matcher, name) {
+        matcher.send_text(&reply).await;
+        return;
+    }
+
+    let action = format!(
+        "在{}{} {}",
+        switch::scope_text(&group_id),
+        switch::state_text(enable),
+        name
+    );
+    if !confirm(matcher, &action).await {
+        return;
+    }
+    audit(event, command, &args.join(" "));
+    switch::set_switch(matcher, name, group_id, enable).await;
+}
+
+async fn broadcast(event: &MessageEvent, matcher: &Matcher<MessageEvent>, message: &str) {
+    if message.is_empty() {
+        matcher.send_text("用法：broadcast <消息>").await;
+        return;
+    }
+    let (Some(bot), Some(groups)) = (&matcher.bot, matcher.get_group_list().await) else {
+        matcher.send_text("获取群列表失败").await;
+        return;
+    };
+    if !confirm(
+        matcher,
+        &format!("向 {} 个群发送：{}", groups.len(), message),
+    )
+    .await
+    {
+        return;
+    }
+    audit(event, Command::Broadcast, message);
+    for group in &groups {
+        bot.send_group_msg(&group.group_id, vec![Message::text(message.to_string())])
+            .await;
+    }
+    matcher
+        .send_text(&format!("已向 {} 个群发送", groups.len()))
+        .await;
+}
+
+async fn leave(event: &MessageEvent, matcher: &Matcher<MessageEvent>, args: &[&str]) {
+    let Some(&group_id) = args.first() else {
+        matcher.send_text("用法：leave <群号>").await;
+        return;
+    };
+    if !confirm(matcher, &format!("退出群 {}", group_id)).await {
+        return;
+    }
+    audit(event, Command::Leave, group_id);
+    matcher.set_group_leave(group_id.to_string(), false).await;
+    matcher.send_text(&format!("已退出群 {}", group_id)).await;
+}
+
+/// superuser 管理命令 Matcher
+///
+/// 包括 `plugins`、`matchers`、`enable|disable <Matcher> [群号|default]`、
+/// `broadcast <消息>`、`reload`、`leave <群号>` 与 `restart`，
+/// 变更操作需回复 y 确认，确认后输出 INFO 日志
+pub fn admin() -> Vec<Matcher<MessageEvent>> {
+    [
+        Command::Plugins,
+        Command::Matchers,
+        Command::Enable,
+        Command::Disable,
+        Command::Broadcast,
+        Command::Reload,
+        Command::Leave,
+        Command::Restart,
+    ]
+    .into_iter()
+    .map(|command| {
+        Matcher::new(command.name(), Admin(command))
+            .set_priority(ADMIN_PRIORITY)
+            .add_rule(rules::is_superuser())
+            .add_pre_matcher(prematchers::command_start())
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn admin_test() {
+    use crate::api::Api;
+    use crate::testing::{MessageBuilder, TestBot, TEST_BOT_ID};
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers.add_message_matchers(admin());
+    let mut config = crate::config::BotConfig::default();
+    config.bot_id = TEST_BOT_ID.to_string();
+    config.superusers = vec!["1".to_string()];
+    let mut bot = TestBot::with_config(config);
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::group("2", "3", "leave 2").build());
+    bot.assert_no_api().await;
+
+    bot.send_message(MessageBuilder::group("2", "1", "leave 4").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("确认退出群 4？回复 y 确认")
+    );
+    bot.send_message(MessageBuilder::group("2", "1", "n").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("已取消"));
+
+    bot.send_message(MessageBuilder::group("2", "1", "leave 4").build());
+    bot.next_reply_text().await;
+    bot.send_message(MessageBuilder::group("2", "1", "y").build());
+    assert!(matches!(
+        bot.next_api().await,
+        Some(Api::SetGroupLeave { params, .. }) if params.group_id == "4"
+    ));
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("已退出群 4"));
+}
+
+#[tokio::test]
+async fn admin_switch_test() {
+    use crate::testing::{MessageBuilder, TestBot, TEST_BOT_ID};
+
+    async fn ping(reply: Reply) {
+        reply.text("pong").await;
+    }
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers
+        .add_message_matchers(admin())
+        .add_message_matcher(Matcher::new(
+            "Ping",
+            FnHandler::new(ping).command(&["ping"]),
+        ));
+    let mut config = crate::config::BotConfig::default();
+    config.bot_id = TEST_BOT_ID.to_string();
+    config.superusers = vec!["1".to_string()];
+    let mut bot = TestBot::with_config(config);
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::group("2", "1", "disable AdminLeave").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("不能开关 AdminLeave")
+    );
+    bot.send_message(MessageBuilder::group("2", "1", "disable Pong").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("未找到 Pong"));
+
+    bot.send_message(MessageBuilder::group("2", "1", "disable Ping").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("确认在群 2禁用 Ping？回复 y 确认")
+    );
+    bot.send_message(MessageBuilder::group("2", "1", "n").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("已取消"));
+    assert!(bot.next_action().await.is_none());
+
+    bot.send_message(MessageBuilder::private("1", "enable Ping default").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("确认在默认启用 Ping？回复 y 确认")
+    );
+    bot.send_message(MessageBuilder::private("1", "y").build());
+    assert!(matches!(
+        bot.next_action().await,
+        Some(Action::DisableMatcher { name, group_id: None, disable: false }) if name == "Ping"
+    ));
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("已在默认启用 Ping")
+    );
+}
+
+#[tokio::test]
+async fn admin_broadcast_test() {
+    use crate::api::Api;
+    use crate::api_resp::{ApiResp, GroupListItem, RespData};
+    use crate::testing::{MessageBuilder, TestBot, TEST_BOT_ID};
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers.add_message_matchers(admin());
+    let mut config = crate::config::BotConfig::default();
+    config.bot_id = TEST_BOT_ID.to_string();
+    config.superusers = vec!["1".to_string()];
+    let mut bot = TestBot::with_config(config);
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::private("1", "broadcast 维护通知").build());
+    let Some(Api::GetGroupList { echo, .. }) = bot.next_api().await else {
+        panic!("get_group_list not called");
+    };
+    let group = |group_id: &str| GroupListItem {
+        group_id: group_id.to_string(),
+        group_name: String::default(),
+        member_count: 0,
+        max_member_count: 0,
+    };
+    bot.respond(ApiResp {
+        status: "ok".to_string(),
+        retcode: 0,
+        data: RespData::GroupList(vec![group("2"), group("3")]),
+        echo,
+    });
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("确认向 2 个群发送：维护通知？回复 y 确认")
+    );
+    bot.send_message(MessageBuilder::private("1", "y").build());
+    for group_id in ["2", "3"] {
+        assert!(matches!(
+            bot.next_api().await,
+            Some(Api::SendGroupMsg { params, .. }) if params.group_id == group_id
+        ));
+    }
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("已向 2 个群发送")
+    );
+}
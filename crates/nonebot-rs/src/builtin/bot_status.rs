@@ -6,10 +6,11 @@ struct Status;
 #[async_trait]
 impl Handler<MessageEvent> for Status {
     crate::on_command!(MessageEvent, "status");
-    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) {
+    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
         matcher
             .send_text(&build_status(&event, &matcher).await)
             .await;
+        Ok(Flow::Stop)
     }
 }
 
@@ -28,13 +29,22 @@ async fn build_status(event: &MessageEvent, matcher: &Matcher<MessageEvent>) ->
     } else {
         "-".to_string()
     };
-    format!(
+    let mut status = format!(
         "当前BotId：{}\n已连接时间：{}\n已加载好友数量：{}\n已加载群数量：{}",
         event.get_self_id(),
         time,
         friend_count,
         group_count
-    )
+    );
+    if let Some(health) = matcher.get_state::<crate::supervisor::PluginHealthTable>() {
+        for (_, h) in health.all() {
+            status.push_str(&format!("\n插件 {}：{}", h.name, h.status));
+            if h.restarts > 0 {
+                status.push_str(&format!("（已重启 {} 次）", h.restarts));
+            }
+        }
+    }
+    status
 }
 
 pub fn bot_status() -> Matcher<MessageEvent> {
@@ -0,0 +1,65 @@
+use crate::utils::timestamp;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{event, Level};
+
+/// 按用户计算的触发冷却
+///
+/// Clone 后共享同一冷却记录
+#[derive(Debug, Clone, Default)]
+pub struct Cooldown {
+    /// 冷却时间（秒），为 0 时不限制
+    secs: i64,
+    /// 各用户上次触发时间戳
+    last: Arc<Mutex<HashMap<String, i64>>>,
+}
+
+impl Cooldown {
+    /// 新建冷却时间为 secs 秒的 Cooldown
+    pub fn new(secs: i64) -> Self {
+        Cooldown {
+            secs,
+            last: Default::default(),
+        }
+    }
+
+    /// 判定用户是否已冷却，已冷却时记录本次触发时间并清除已冷却用户的记录
+    pub fn check(&self, user_id: &str) -> bool {
+        if self.secs <= 0 {
+            return true;
+        }
+        let now = timestamp();
+        let mut last = self.last.lock().unwrap();
+        match last.get(user_id) {
+            Some(t) if now - t < self.secs => {
+                event!(Level::DEBUG, "User {} in cooldown", user_id);
+                false
+            }
+            _ => {
+                last.retain(|_, t| now - *t < self.secs);
+                last.insert(user_id.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+#[test]
+fn cooldown_test() {
+    let cooldown = Cooldown::new(60);
+    assert!(cooldown.check("1"));
+    assert!(!cooldown.check("1"));
+    assert!(cooldown.check("2"));
+    cooldown
+        .last
+        .lock()
+        .unwrap()
+        .insert("3".to_string(), timestamp() - 60);
+    assert!(cooldown.check("4"));
+    let last = cooldown.last.lock().unwrap();
+    assert!(!last.contains_key("3"));
+    assert_eq!(last.len(), 3);
+    drop(last);
+    assert!(Cooldown::new(0).check("1"));
+    assert!(Cooldown::new(0).check("1"));
+}
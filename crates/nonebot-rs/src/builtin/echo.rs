@@ -8,11 +8,12 @@ pub struct Echo {}
 #[async_trait]
 impl Handler<MessageEvent> for Echo {
     on_command!(MessageEvent, "echo", "Echo");
-    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) {
+    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
         let msg = Message::Text {
             text: event.get_raw_message().to_string(),
         };
         matcher.send(vec![msg]).await;
+        Ok(Flow::Stop)
     }
 }
 
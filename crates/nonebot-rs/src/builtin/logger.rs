@@ -2,7 +2,6 @@ use crate::{
     event::{Event, MessageEvent, MetaEvent},
     BotGetter, EventReceiver,
 };
-use colored::*;
 use tokio::task::JoinHandle;
 use tracing::{event, Level};
 use uuid::{uuid, Uuid};
@@ -16,36 +15,23 @@ pub const PLUGIN_ID: Uuid = uuid!("b86ad211-5bd5-42e8-8a74-4a40f37b78c1");
 /// Message Event Logger
 pub fn message_logger(event: &MessageEvent) {
     match &event {
-        MessageEvent::Private(p) => {
-            let mut user_id = p.user_id.to_string();
-            while user_id.len() < 10 {
-                user_id.insert(0, ' ');
-            }
-            event!(
-                Level::INFO,
-                "{} [{}] -> {} from {}({})",
-                user_id.green(),
-                p.self_id.to_string().red(),
-                p.raw_message,
-                p.sender.nickname.to_string().blue(),
-                p.user_id.to_string().green(),
-            )
-        }
-        MessageEvent::Group(g) => {
-            let mut group_id = g.group_id.to_string();
-            while group_id.len() < 10 {
-                group_id.insert(0, ' ');
-            }
-            event!(
-                Level::INFO,
-                "{} [{}] -> {} from {}({})",
-                group_id.magenta(),
-                g.self_id.to_string().red(),
-                g.raw_message,
-                g.sender.nickname.to_string().blue(),
-                g.user_id.to_string().green(),
-            )
-        }
+        MessageEvent::Private(p) => event!(
+            Level::INFO,
+            bot_id = %p.self_id,
+            user_id = %p.user_id,
+            nickname = %p.sender.nickname,
+            "{}",
+            p.raw_message
+        ),
+        MessageEvent::Group(g) => event!(
+            Level::INFO,
+            bot_id = %g.self_id,
+            group_id = %g.group_id,
+            user_id = %g.user_id,
+            nickname = %g.sender.nickname,
+            "{}",
+            g.raw_message
+        ),
     }
 }
 
@@ -60,8 +46,9 @@ pub fn meta_logger(event: &MetaEvent) {
 pub struct Logger;
 
 impl Logger {
-    async fn event_recv(self, mut event_receiver: EventReceiver) {
-        while let Ok(event) = event_receiver.recv().await {
+    async fn event_recv(self, event_receiver: EventReceiver) {
+        let mut events = crate::EventStream::new(PLUGIN_NAME, event_receiver);
+        while let Some(event) = events.recv().await {
             match &event {
                 Event::Message(m) => message_logger(m),
                 Event::Meta(m) => meta_logger(m),
@@ -72,6 +59,8 @@ impl Logger {
 }
 
 impl crate::Plugin for Logger {
+    type Config = ();
+
     fn load(&self, event_receiver: EventReceiver, _: BotGetter) -> JoinHandle<()> {
         let logger = self.clone();
         tokio::spawn(logger.event_recv(event_receiver))
@@ -78,8 +78,9 @@ macro_rules! matcher_request {
         #[async_trait]
         impl Handler<MessageEvent> for Temp {
             on_match_all!();
-            async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) {
+            async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
                 $b
+                Ok(Flow::Stop)
             }
         }
 
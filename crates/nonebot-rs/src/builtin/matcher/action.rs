@@ -1,17 +1,76 @@
-use colored::*;
 use tracing::{event, Level};
 
 /// Matchers 内部 Action
 #[derive(Clone, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum MatchersAction {
     /// 添加 MessageEvent Matcher
     AddMessageEventMatcher {
         message_event_matcher: super::Matcher<crate::event::MessageEvent>,
     },
+    /// 添加 NoticeEvent Matcher
+    AddNoticeEventMatcher {
+        notice_event_matcher: super::Matcher<crate::event::NoticeEvent>,
+    },
+    /// 添加 RequestEvent Matcher
+    AddRequestEventMatcher {
+        request_event_matcher: super::Matcher<crate::event::RequestEvent>,
+    },
+    /// 添加 MetaEvent Matcher
+    AddMetaEventMatcher {
+        meta_event_matcher: super::Matcher<crate::event::MetaEvent>,
+    },
     /// 移除 Matcher
     RemoveMatcher { matcher_name: String },
 }
 
+/// 可由 Matcher 通过 MatchersAction 动态添加 Matcher 的 Event 类型
+pub trait MatcherEvent: Clone + Sized {
+    /// 构建添加该类型 Matcher 的 MatchersAction
+    fn add_matcher_action(matcher: super::Matcher<Self>) -> MatchersAction;
+
+    /// 为 MessageEvent 时返回自身，用于向用户回复错误信息
+    fn as_message_event(&self) -> Option<&crate::event::MessageEvent> {
+        None
+    }
+}
+
+impl MatcherEvent for crate::event::MessageEvent {
+    fn add_matcher_action(matcher: super::Matcher<Self>) -> MatchersAction {
+        MatchersAction::AddMessageEventMatcher {
+            message_event_matcher: matcher,
+        }
+    }
+
+    fn as_message_event(&self) -> Option<&crate::event::MessageEvent> {
+        Some(self)
+    }
+}
+
+impl MatcherEvent for crate::event::NoticeEvent {
+    fn add_matcher_action(matcher: super::Matcher<Self>) -> MatchersAction {
+        MatchersAction::AddNoticeEventMatcher {
+            notice_event_matcher: matcher,
+        }
+    }
+}
+
+impl MatcherEvent for crate::event::RequestEvent {
+    fn add_matcher_action(matcher: super::Matcher<Self>) -> MatchersAction {
+        MatchersAction::AddRequestEventMatcher {
+            request_event_matcher: matcher,
+        }
+    }
+}
+
+impl MatcherEvent for crate::event::MetaEvent {
+    fn add_matcher_action(matcher: super::Matcher<Self>) -> MatchersAction {
+        MatchersAction::AddMetaEventMatcher {
+            meta_event_matcher: matcher,
+        }
+    }
+}
+
 impl super::matchers::Matchers {
     /// Matchers 处理 action method
     pub fn handle_action(&mut self, action: MatchersAction) {
@@ -21,19 +80,44 @@ impl super::matchers::Matchers {
             } => {
                 event!(
                     Level::DEBUG,
-                    "Adding Message Event Matcher: {}",
-                    message_event_matcher.name.blue()
+                    matcher = %message_event_matcher.name,
+                    "Adding Message Event Matcher"
                 );
                 self.add_message_matcher(message_event_matcher);
             }
-            MatchersAction::RemoveMatcher { matcher_name } => {
+            MatchersAction::AddNoticeEventMatcher {
+                notice_event_matcher,
+            } => {
                 event!(
                     Level::DEBUG,
-                    "Removing Message Event Matcher: {}",
-                    matcher_name.blue()
+                    matcher = %notice_event_matcher.name,
+                    "Adding Notice Event Matcher"
                 );
+                self.add_notice_matcher(notice_event_matcher);
+            }
+            MatchersAction::AddRequestEventMatcher {
+                request_event_matcher,
+            } => {
+                event!(
+                    Level::DEBUG,
+                    matcher = %request_event_matcher.name,
+                    "Adding Request Event Matcher"
+                );
+                self.add_request_matcher(request_event_matcher);
+            }
+            MatchersAction::AddMetaEventMatcher { meta_event_matcher } => {
+                event!(
+                    Level::DEBUG,
+                    matcher = %meta_event_matcher.name,
+                    "Adding Meta Event Matcher"
+                );
+                self.add_meta_matcher(meta_event_matcher);
+            }
+            MatchersAction::RemoveMatcher { matcher_name } => {
+                event!(Level::DEBUG, matcher = %matcher_name, "Removing Matcher");
                 self.remove_matcher(&matcher_name);
             }
         }
+        self.publish();
     }
 }
@@ -1,7 +1,6 @@
 use super::Matcher;
 use crate::api_resp;
 use crate::event::SelfId;
-use colored::*;
 use tracing::{event, Level};
 
 macro_rules! no_resp_api {
@@ -12,9 +11,9 @@ macro_rules! no_resp_api {
             } else {
                 event!(
                     Level::ERROR,
-                    "Calling api {} {}",
-                    stringify!($fn_name).blue(),
-                    "with unbuilt matcher!".red()
+                    matcher = %self.name,
+                    "Calling api {} with unbuilt matcher!",
+                    stringify!($fn_name)
                 );
             }
         }
@@ -26,9 +25,9 @@ macro_rules! no_resp_api {
             } else {
                 event!(
                     Level::ERROR,
-                    "Calling api {} {}",
-                    stringify!($fn_name).blue(),
-                    "with unbuilt matcher!".red()
+                    matcher = %self.name,
+                    "Calling api {} with unbuilt matcher!",
+                    stringify!($fn_name)
                 );
             }
         }
@@ -43,9 +42,9 @@ macro_rules! resp_api {
             } else {
                 event!(
                     Level::ERROR,
-                    "Calling api {} {}",
-                    stringify!($fn_name).blue(),
-                    "with unbuilt matcher!".red()
+                    matcher = %self.name,
+                    "Calling api {} with unbuilt matcher!",
+                    stringify!($fn_name)
                 );
                 None
             }
@@ -58,9 +57,9 @@ macro_rules! resp_api {
             } else {
                 event!(
                     Level::ERROR,
-                    "Calling api {} {}",
-                    stringify!($fn_name).blue(),
-                    "with unbuilt matcher!".red()
+                    matcher = %self.name,
+                    "Calling api {} with unbuilt matcher!",
+                    stringify!($fn_name)
                 );
                 None
             }
@@ -73,9 +72,9 @@ macro_rules! resp_api {
             } else {
                 event!(
                     Level::ERROR,
-                    "Calling api {} {}",
-                    stringify!($fn_name).blue(),
-                    "with unbuilt matcher!".red()
+                    matcher = %self.name,
+                    "Calling api {} with unbuilt matcher!",
+                    stringify!($fn_name)
                 );
                 None
             }
@@ -94,8 +93,8 @@ where
         } else {
             event!(
                 Level::ERROR,
-                "{}",
-                "Calling api with unbuilt matcher!".red()
+                matcher = %self.name,
+                "Calling api with unbuilt matcher!"
             );
         }
     }
@@ -107,8 +106,8 @@ where
         } else {
             event!(
                 Level::ERROR,
-                "{}",
-                "Calling api with unbuilt matcher!".red()
+                matcher = %self.name,
+                "Calling api with unbuilt matcher!"
             );
             None
         }
@@ -0,0 +1,326 @@
+use super::{Flow, Handler, HandlerResult, Matcher};
+use crate::event::MessageEvent;
+use crate::message::Message;
+use crate::state::State;
+use async_trait::async_trait;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// 从 MessageEvent 与 Matcher 中提取 handler 函数参数
+///
+/// 返回 None 时 handler 函数不会运行，事件继续向下一级传递
+pub trait FromEvent: Sized {
+    fn from_event(event: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self>;
+}
+
+/// 命令参数，按空白字符分割命令后的 raw_message
+#[derive(Debug, Clone)]
+pub struct Args(pub Vec<String>);
+
+/// 命令后的完整文本（已去除前方空格）
+#[derive(Debug, Clone)]
+pub struct RawMessage(pub String);
+
+/// 群号，仅群消息可提取
+#[derive(Debug, Clone)]
+pub struct GroupId(pub String);
+
+/// 消息段
+#[derive(Debug, Clone)]
+pub struct MessageChain(pub Vec<Message>);
+
+/// 消息发送者
+#[derive(Debug, Clone)]
+pub struct Sender {
+    /// 发送者 QQ 号
+    pub user_id: String,
+    /// 昵称
+    pub nickname: String,
+    /// 群角色 owner|admin|member，私聊为 None
+    pub role: Option<String>,
+}
+
+/// 回复当前消息
+#[derive(Debug, Clone)]
+pub struct Reply(pub Matcher<MessageEvent>);
+
+impl Reply {
+    /// 回复纯文本消息
+    pub async fn text(&self, msg: &str) {
+        self.0.send_text(msg).await;
+    }
+
+    /// 回复 Vec<Message> 消息
+    pub async fn send(&self, msg: Vec<Message>) {
+        self.0.send(msg).await;
+    }
+}
+
+impl FromEvent for Args {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(Args(
+            event
+                .get_raw_message()
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect(),
+        ))
+    }
+}
+
+impl FromEvent for RawMessage {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(RawMessage(crate::utils::remove_space(
+            event.get_raw_message(),
+        )))
+    }
+}
+
+impl FromEvent for GroupId {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        match event {
+            MessageEvent::Group(g) => Some(GroupId(g.group_id.clone())),
+            MessageEvent::Private(_) => None,
+        }
+    }
+}
+
+impl FromEvent for MessageChain {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(MessageChain(event.get_message().clone()))
+    }
+}
+
+impl FromEvent for Sender {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(match event {
+            MessageEvent::Private(p) => Sender {
+                user_id: p.user_id.clone(),
+                nickname: p.sender.nickname.clone(),
+                role: None,
+            },
+            MessageEvent::Group(g) => Sender {
+                user_id: g.user_id.clone(),
+                nickname: g.sender.nickname.clone(),
+                role: Some(g.sender.role.clone()),
+            },
+        })
+    }
+}
+
+impl FromEvent for Reply {
+    fn from_event(_: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(Reply(matcher.clone()))
+    }
+}
+
+impl FromEvent for MessageEvent {
+    fn from_event(event: &MessageEvent, _: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(event.clone())
+    }
+}
+
+impl FromEvent for Matcher<MessageEvent> {
+    fn from_event(_: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(matcher.clone())
+    }
+}
+
+impl FromEvent for crate::Bot {
+    fn from_event(_: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self> {
+        matcher.bot.clone()
+    }
+}
+
+impl<T> FromEvent for State<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn from_event(_: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self> {
+        matcher.get_state::<T>()
+    }
+}
+
+impl<T> FromEvent for Option<T>
+where
+    T: FromEvent,
+{
+    fn from_event(event: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<Self> {
+        Some(T::from_event(event, matcher))
+    }
+}
+
+/// handler 函数返回值
+pub trait IntoHandlerResult {
+    fn into_handler_result(self) -> HandlerResult;
+}
+
+impl IntoHandlerResult for () {
+    fn into_handler_result(self) -> HandlerResult {
+        Ok(Flow::Stop)
+    }
+}
+
+impl IntoHandlerResult for Flow {
+    fn into_handler_result(self) -> HandlerResult {
+        Ok(self)
+    }
+}
+
+impl<E> IntoHandlerResult for Result<Flow, E>
+where
+    E: Into<super::HandlerError>,
+{
+    fn into_handler_result(self) -> HandlerResult {
+        self.map_err(Into::into)
+    }
+}
+
+/// handler 函数 Future
+pub type HandlerFuture = Pin<Box<dyn Future<Output = HandlerResult> + Send>>;
+
+/// 参数均实现 `FromEvent` 的 async handler 函数
+pub trait HandlerFn<Args>: Send + Sync + 'static {
+    /// 提取参数并调用，任一参数提取失败返回 None
+    fn call(&self, event: &MessageEvent, matcher: &Matcher<MessageEvent>) -> Option<HandlerFuture>;
+}
+
+macro_rules! impl_handler_fn {
+    ($($ty: ident),*) => {
+        #[allow(non_snake_case, unused_variables)]
+        impl<F, Fut, R, $($ty,)*> HandlerFn<($($ty,)*)> for F
+        where
+            F: Fn($($ty,)*) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = R> + Send + 'static,
+            R: IntoHandlerResult,
+            $($ty: FromEvent + Send + 'static,)*
+        {
+            fn call(
+                &self,
+                event: &MessageEvent,
+                matcher: &Matcher<MessageEvent>,
+            ) -> Option<HandlerFuture> {
+                $(let $ty = $ty::from_event(event, matcher)?;)*
+                let fut = (self)($($ty,)*);
+                Some(Box::pin(async move { fut.await.into_handler_result() }))
+            }
+        }
+    };
+}
+
+impl_handler_fn!();
+impl_handler_fn!(T1);
+impl_handler_fn!(T1, T2);
+impl_handler_fn!(T1, T2, T3);
+impl_handler_fn!(T1, T2, T3, T4);
+impl_handler_fn!(T1, T2, T3, T4, T5);
+impl_handler_fn!(T1, T2, T3, T4, T5, T6);
+
+/// 将 async handler 函数适配为 `Handler<MessageEvent>`
+///
+/// ```ignore
+/// async fn echo(Args(args): Args, reply: Reply) {
+///     reply.text(&args.join(" ")).await;
+/// }
+///
+/// Matcher::new("Echo", FnHandler::new(echo).command(&["echo"]));
+/// ```
+pub struct FnHandler<F, Args> {
+    f: F,
+    commands: Vec<String>,
+    _args: PhantomData<fn() -> Args>,
+}
+
+impl<F, Args> FnHandler<F, Args>
+where
+    F: HandlerFn<Args>,
+{
+    /// 新建匹配所有消息的 FnHandler
+    pub fn new(f: F) -> Self {
+        FnHandler {
+            f,
+            commands: vec![],
+            _args: PhantomData,
+        }
+    }
+
+    /// 设置命令，匹配的命令将从 `raw_message` 中移除
+    pub fn command(mut self, commands: &[&str]) -> Self {
+        self.commands = commands.iter().map(|c| c.to_string()).collect();
+        self
+    }
+}
+
+#[async_trait]
+impl<F, Args> Handler<MessageEvent> for FnHandler<F, Args>
+where
+    F: HandlerFn<Args>,
+    Args: 'static,
+{
+    fn match_(&self, event: &mut MessageEvent) -> bool {
+        if self.commands.is_empty() {
+            return true;
+        }
+        for command in &self.commands {
+            if let Some(raw_message) = event.get_raw_message().strip_prefix(command.as_str()) {
+                event.set_raw_message(raw_message.to_string());
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+        match self.f.call(&event, &matcher) {
+            Some(fut) => fut.await,
+            None => Ok(Flow::Continue),
+        }
+    }
+}
+
+#[tokio::test]
+async fn fn_handler_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLED: AtomicUsize = AtomicUsize::new(0);
+
+    async fn handler(Args(args): Args, GroupId(group_id): GroupId, sender: Sender) -> Flow {
+        assert_eq!(args, vec!["hello", "world"]);
+        assert_eq!(group_id, "101");
+        assert_eq!(sender.role.as_deref(), Some("member"));
+        CALLED.fetch_add(1, Ordering::SeqCst);
+        Flow::Continue
+    }
+
+    let test_str = "{\"post_type\":\"message\",\"message_type\":\"group\",\"sub_type\":\"normal\",\"time\":1631193409,\"self_id\":11,\"message_id\":111,\"group_id\":101,\"user_id\":12,\"message\":[{\"type\":\"text\",\"data\":{\"text\":\"echo hello world\"}}],\"raw_message\":\"echo hello world\",\"font\":0,\"sender\":{\"user_id\":12,\"nickname\":\"nick\",\"card\":\"\",\"sex\":\"unknown\",\"age\":0,\"area\":\"\",\"level\":\"\",\"role\":\"member\",\"title\":\"\"}}";
+    let mut event: MessageEvent = match serde_json::from_str(test_str).unwrap() {
+        crate::event::Event::Message(e) => e,
+        _ => unreachable!(),
+    };
+    let fn_handler = FnHandler::new(handler).command(&["echo"]);
+    assert!(fn_handler.match_(&mut event));
+    let matcher = Matcher::new("Echo", FnHandler::new(handler));
+    let flow = fn_handler.handle(event, matcher).await.unwrap();
+    assert_eq!(flow, Flow::Continue);
+    assert_eq!(CALLED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn command_macro_test() {
+    #[crate::command("loli", aliases = ["lolicon"], permission = group, priority = 2)]
+    async fn lolicon(Args(_): Args) {}
+
+    let mut event: MessageEvent = match serde_json::from_str("{\"post_type\":\"message\",\"message_type\":\"private\",\"sub_type\":\"friend\",\"time\":1631193409,\"self_id\":11,\"message_id\":111,\"user_id\":12,\"message\":[],\"raw_message\":\"lolicon 1\",\"font\":0,\"sender\":{\"user_id\":12,\"nickname\":\"nick\",\"sex\":\"unknown\",\"age\":0}}").unwrap() {
+        crate::event::Event::Message(e) => e,
+        _ => unreachable!(),
+    };
+    let matcher = lolicon();
+    assert_eq!(matcher.name, "Lolicon");
+    assert_eq!(matcher.priority, 2);
+    assert!(Lolicon {
+        cooldown: Default::default()
+    }
+    .match_(&mut event));
+    assert_eq!(event.get_raw_message(), " 1");
+}
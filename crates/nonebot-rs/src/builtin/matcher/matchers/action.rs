@@ -1,6 +1,9 @@
-use super::{Matchers, MatchersBTreeMap, MatchersHashMap};
+use super::{Matchers, MatchersBTreeMap, MatchersHashMap, Switches};
+use crate::builtin::matcher::extract::{FnHandler, HandlerFn};
 use crate::builtin::matcher::{action::MatchersAction, Matcher};
+use crate::builtin::prematchers;
 use crate::event::{MessageEvent, MetaEvent, NoticeEvent, RequestEvent};
+use crate::state::StateMap;
 use std::collections::{BTreeMap, HashMap};
 use tokio::sync::broadcast;
 
@@ -13,14 +16,36 @@ impl Matchers {
         meta: Option<MatchersBTreeMap<MetaEvent>>,
     ) -> Matchers {
         let (sender, _) = broadcast::channel(32);
-        Matchers {
-            message: unoptionb(&message),
-            notice: unoptionb(&notice),
-            request: unoptionb(&request),
-            meta: unoptionb(&meta),
+        let mut matchers = Matchers {
+            message: BTreeMap::new(),
+            notice: BTreeMap::new(),
+            request: BTreeMap::new(),
+            meta: BTreeMap::new(),
             bot_getter: None,
             action_sender: sender,
+            switches: Default::default(),
+            state: StateMap::new(),
+            id: uuid::Uuid::new_v4(),
+        };
+        for matcherh in unoptionb(&message).into_values() {
+            matchers.add_message_matchers(matcherh.into_values().collect());
         }
+        for matcherh in unoptionb(&notice).into_values() {
+            for matcher in matcherh.into_values() {
+                matchers.add_notice_matcher(matcher);
+            }
+        }
+        for matcherh in unoptionb(&request).into_values() {
+            for matcher in matcherh.into_values() {
+                matchers.add_request_matcher(matcher);
+            }
+        }
+        for matcherh in unoptionb(&meta).into_values() {
+            for matcher in matcherh.into_values() {
+                matchers.add_meta_matcher(matcher);
+            }
+        }
+        matchers
     }
 
     /// 新建空 Matchers
@@ -33,6 +58,8 @@ impl Matchers {
         self.notice = m.notice.clone();
         self.request = m.request.clone();
         self.meta = m.meta.clone();
+        self.switches = m.switches.clone();
+        self.state = m.state.clone();
     }
 
     /// Bot 连接时运行所有 Matcher on_bot_connect 方法
@@ -69,10 +96,17 @@ impl Matchers {
         matcherb: &mut MatchersBTreeMap<E>,
         mut matcher: Matcher<E>,
         action_sender: broadcast::Sender<MatchersAction>,
+        switches: Switches,
+        state: StateMap,
     ) where
         E: Clone,
     {
+        if !matcher.is_temp() {
+            switches.write().unwrap().register(&matcher.name);
+        }
         matcher.set_action_sender(action_sender);
+        matcher.set_switches(switches);
+        matcher.set_state(state);
         match matcherb.get_mut(&matcher.priority) {
             Some(h) => {
                 h.insert(matcher.name.clone(), matcher);
@@ -87,7 +121,13 @@ impl Matchers {
 
     /// 向 Matchers 添加 Matcher<MessageEvent>
     pub fn add_message_matcher(&mut self, matcher: Matcher<MessageEvent>) -> &mut Self {
-        Matchers::add_matcher(&mut self.message, matcher, self.action_sender.clone());
+        Matchers::add_matcher(
+            &mut self.message,
+            matcher,
+            self.action_sender.clone(),
+            self.switches.clone(),
+            self.state.clone(),
+        );
         self
     }
 
@@ -99,41 +139,113 @@ impl Matchers {
         self
     }
 
+    /// 向 Matchers 添加以 async fn 为 handler 的命令 Matcher
+    ///
+    /// handler 函数参数需实现 `FromEvent`，命令需以 `command_start` 开头
+    pub fn add_command<F, Args>(&mut self, name: &str, commands: &[&str], f: F) -> &mut Self
+    where
+        F: HandlerFn<Args>,
+        Args: 'static,
+    {
+        self.add_message_matcher(
+            Matcher::new(name, FnHandler::new(f).command(commands))
+                .add_pre_matcher(prematchers::command_start()),
+        )
+    }
+
+    /// 向 Matchers 添加共享状态，可在 handler 函数中以 `State<T>` 提取
+    pub fn manage<T>(&mut self, value: T) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.state.insert(value);
+        self
+    }
+
+    /// 替换 Matchers 共享状态，已添加的状态将并入新状态表
+    ///
+    /// 添加到 Nonebot 时调用，使 Matcher 可获取 Nonebot 共享状态
+    pub fn set_shared_state(&mut self, state: StateMap) {
+        fn set_state_<E>(matcherb: &mut MatchersBTreeMap<E>, state: &StateMap)
+        where
+            E: Clone,
+        {
+            for matcherh in matcherb.values_mut() {
+                for matcher in matcherh.values_mut() {
+                    matcher.set_state(state.clone());
+                }
+            }
+        }
+
+        state.merge(&self.state);
+        set_state_(&mut self.message, &state);
+        set_state_(&mut self.notice, &state);
+        set_state_(&mut self.request, &state);
+        set_state_(&mut self.meta, &state);
+        self.state = state;
+    }
+
     /// 向 Matchers 添加 Matcher<NoticeEvent>
     pub fn add_notice_matcher(&mut self, matcher: Matcher<NoticeEvent>) -> &mut Self {
-        Matchers::add_matcher(&mut self.notice, matcher, self.action_sender.clone());
+        Matchers::add_matcher(
+            &mut self.notice,
+            matcher,
+            self.action_sender.clone(),
+            self.switches.clone(),
+            self.state.clone(),
+        );
         self
     }
 
     /// 向 Matchers 添加 Matcher<RequestEvent>
     pub fn add_request_matcher(&mut self, matcher: Matcher<RequestEvent>) -> &mut Self {
-        Matchers::add_matcher(&mut self.request, matcher, self.action_sender.clone());
+        Matchers::add_matcher(
+            &mut self.request,
+            matcher,
+            self.action_sender.clone(),
+            self.switches.clone(),
+            self.state.clone(),
+        );
         self
     }
 
     /// 向 Matchers 添加 Matcher<MetaEvent>
     pub fn add_meta_matcher(&mut self, matcher: Matcher<MetaEvent>) -> &mut Self {
-        Matchers::add_matcher(&mut self.meta, matcher, self.action_sender.clone());
+        Matchers::add_matcher(
+            &mut self.meta,
+            matcher,
+            self.action_sender.clone(),
+            self.switches.clone(),
+            self.state.clone(),
+        );
         self
     }
 
     /// 根据 Matcher.name 从 Matchers 移除 Matcher
     pub fn remove_matcher(&mut self, name: &str) {
-        fn remove_matcher_<E>(matcherb: &mut MatchersBTreeMap<E>, name: &str)
+        /// 返回被移除的 Matcher 是否为临时 Matcher
+        fn remove_matcher_<E>(matcherb: &mut MatchersBTreeMap<E>, name: &str) -> Option<bool>
         where
             E: Clone,
         {
             for (_, matcherh) in matcherb.iter_mut() {
-                if matcherh.remove(name).is_some() {
-                    return;
+                if let Some(matcher) = matcherh.remove(name) {
+                    return Some(matcher.is_temp());
                 }
             }
+            None
         }
 
-        remove_matcher_(&mut self.message, name);
-        remove_matcher_(&mut self.notice, name);
-        remove_matcher_(&mut self.request, name);
-        remove_matcher_(&mut self.meta, name);
+        let removed = [
+            remove_matcher_(&mut self.message, name),
+            remove_matcher_(&mut self.notice, name),
+            remove_matcher_(&mut self.request, name),
+            remove_matcher_(&mut self.meta, name),
+        ];
+        // 临时 Matcher 不注册开关
+        if removed.contains(&Some(false)) {
+            self.switches.write().unwrap().unregister(name);
+        }
     }
 
     /// 根据 Matcher.name disable Matcher
@@ -154,6 +266,23 @@ impl Matchers {
         disable_matcher_(&mut self.request, name, disable);
         disable_matcher_(&mut self.meta, name, disable);
     }
+
+    /// 判定 Matcher 在指定群是否启用
+    pub fn is_switch_on(&self, name: &str, group_id: Option<&str>) -> bool {
+        self.switches.read().unwrap().is_enabled(name, group_id)
+    }
+
+    /// 设置 Matcher 分群开关并持久化，group_id 为 None 时设置默认开关
+    ///
+    /// Matcher 不存在时返回 false
+    pub async fn set_switch(
+        &self,
+        name: &str,
+        group_id: Option<&str>,
+        enable: bool,
+    ) -> std::io::Result<bool> {
+        super::switch::set_and_save(&self.switches, name, group_id, enable).await
+    }
 }
 
 #[doc(hidden)]
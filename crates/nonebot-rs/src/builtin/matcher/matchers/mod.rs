@@ -1,22 +1,30 @@
-use crate::builtin::matcher::Matcher;
-use crate::event::NoneBotEvent::{BotConnect, BotDisconnect};
-use crate::event::{Event, MessageEvent, MetaEvent, NoticeEvent, RequestEvent, SelfId};
+use crate::builtin::matcher::{action::MatchersAction, Flow, Matcher, MatcherEvent};
+use crate::event::NoneBotEvent::{self, BotConnect, BotDisconnect};
+use crate::event::{
+    Event, GroupId, MessageEvent, MetaEvent, NoticeEvent, RequestEvent, SelfId, UserId,
+};
+use crate::state::StateMap;
 use crate::{BotGetter, EventReceiver, Plugin};
-use colored::*;
 use std::collections::{BTreeMap, HashMap};
 use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
-use tracing::{event, Level};
+use tracing::{event, field::Empty, info_span, Instrument, Level, Span};
 use uuid::{uuid, Uuid};
 
 mod action;
+mod switch;
+mod table;
+
+pub(crate) use switch::set_and_save;
+pub use switch::{MatcherSwitch, MatcherSwitches, Switches};
+pub use table::{MatcherInfo, MatcherTable};
 
 /// 按 `priority` 依序存储 `MatchersHashMap`
 pub type MatchersBTreeMap<E> = BTreeMap<i8, MatchersHashMap<E>>;
 /// 使用唯一名字存储 `Matcher`
 pub type MatchersHashMap<E> = HashMap<String, Matcher<E>>;
 /// Matchers Action Sender
-pub type ActionSender = broadcast::Sender<super::action::MatchersAction>;
+pub type ActionSender = broadcast::Sender<MatchersAction>;
 
 pub const PLUGIN_NAME: &str = "Matcher";
 pub const PLUGIN_AUTHER: &str = "abrahum";
@@ -39,24 +47,42 @@ pub struct Matchers {
     bot_getter: Option<BotGetter>,
     /// Matchers Action Sender
     action_sender: ActionSender,
+    /// Matcher 分群开关
+    switches: Switches,
+    /// Matcher 共享状态
+    state: StateMap,
+    /// 在 `MatcherTable` 中区分 Matchers
+    id: Uuid,
 }
 
 impl Matchers {
+    /// 共享状态中数据根目录下的 Matchers 数据目录
+    fn data_path(&self) -> std::path::PathBuf {
+        self.get_plugin_data_path(&crate::plugin::PluginDataDir::get(&self.state))
+    }
+
     async fn handle_events(&mut self, event: Event, bot: &crate::bot::Bot) {
+        let span = event_span(&event);
         match event {
             Event::Message(e) => {
                 self.handle_event(self.message.clone(), e, bot.clone())
+                    .instrument(span)
                     .await;
             }
             Event::Notice(e) => {
-                self.handle_event(self.notice.clone(), e, bot.clone()).await;
+                self.handle_event(self.notice.clone(), e, bot.clone())
+                    .instrument(span)
+                    .await;
             }
             Event::Request(e) => {
                 self.handle_event(self.request.clone(), e, bot.clone())
+                    .instrument(span)
                     .await;
             }
             Event::Meta(e) => {
-                self.handle_event(self.meta.clone(), e, bot.clone()).await;
+                self.handle_event(self.meta.clone(), e, bot.clone())
+                    .instrument(span)
+                    .await;
             }
             Event::Nonebot(e) => match e {
                 BotConnect { bot } => {
@@ -66,85 +92,232 @@ impl Matchers {
                 BotDisconnect { bot } => {
                     self.run_on_connect(bot, true).await;
                 }
+                _ => {}
             },
         }
     }
 
-    /// 接收按类型分发后的 Event 逐级匹配 Matcher
+    /// 接收按类型分发后的 Event，移除已过期的临时 Matcher 后逐级分发
     async fn handle_event<E>(
         &mut self,
         mut matcherb: MatchersBTreeMap<E>,
         event: E,
         bot: crate::bot::Bot,
     ) where
-        E: Clone + Send + 'static + std::fmt::Debug + SelfId,
+        E: MatcherEvent + Send + Sync + 'static + std::fmt::Debug + SelfId + GroupId,
     {
         event!(Level::TRACE, "handling event {:?}", event);
-        // 根据不同 Event 类型，逐级匹配，判定是否 Block
-        for (_, matcherh) in matcherb.iter_mut() {
-            if self
-                ._handler_event(matcherh, event.clone(), bot.clone())
-                .await
-            {
-                break;
-            };
+        let mut expired = vec![];
+        for matcherh in matcherb.values_mut() {
+            matcherh.retain(|_, matcher| {
+                if matcher.is_expired() {
+                    expired.push(matcher.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        if !expired.is_empty() {
+            for matcher in &expired {
+                event!(Level::DEBUG, matcher = %matcher.name, "Remove expired matcher");
+                self.remove_matcher(&matcher.name);
+                matcher.drop_timeout().await;
+            }
+            self.publish();
         }
+        tokio::spawn(dispatch(matcherb, event, bot, self.switches.clone()).in_current_span());
     }
 
-    #[doc(hidden)]
-    async fn _handler_event<E>(
-        &mut self,
-        matcherh: &mut MatchersHashMap<E>,
-        e: E,
-        bot: crate::bot::Bot,
-    ) -> bool
-    where
-        E: Clone + Send + 'static + std::fmt::Debug + SelfId,
-    {
-        event!(Level::TRACE, "handling event_ {:?}", e);
-        // 每级 Matcher 匹配，返回是否 block
-        let mut get_block = false;
-        let config = bot.config.clone();
-        for (name, matcher) in matcherh.iter_mut() {
-            let matched = matcher
-                .build(bot.clone())
-                .match_(e.clone(), config.clone(), self)
-                .await;
-            if matched {
-                event!(Level::INFO, "Matched {}", name.blue());
-                if matcher.is_block() {
-                    get_block = true;
-                }
-                if matcher.is_temp() {
-                    event!(Level::INFO, "Remove matched temp matcher {}", name.blue());
-                    self.remove_matcher(name);
+    async fn event_recv(
+        mut self,
+        event_receiver: EventReceiver,
+        mut action_receiver: broadcast::Receiver<MatchersAction>,
+    ) {
+        let mut events = crate::EventStream::new("Matchers", event_receiver);
+        loop {
+            tokio::select! {
+                // Action 优先于 Event 处理，保证临时 Matcher 在下一个 Event 前生效
+                biased;
+                action = action_receiver.recv() => match action {
+                    Ok(action) => self.handle_action(action),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        event!(Level::WARN, "Matchers Action lagged, {} actions skipped", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                event = events.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+                    // 分发前依序处理所有已到达的 Action
+                    while let Ok(action) = action_receiver.try_recv() {
+                        self.handle_action(action);
+                    }
+                    if let Event::Nonebot(NoneBotEvent::DisableMatcher { name, group_id, disable }) = &event {
+                        match self.set_switch(name, group_id.as_deref(), !disable).await {
+                            Ok(true) => event!(
+                                Level::INFO,
+                                matcher = %name,
+                                group_id = group_id.as_deref(),
+                                "Matcher is {} in {}",
+                                if *disable { "disabled" } else { "enabled" },
+                                group_id.as_deref().unwrap_or("all groups")
+                            ),
+                            // 不属于此 Matchers 的 Matcher
+                            Ok(false) => {}
+                            Err(e) => event!(Level::ERROR, "Save matcher switch fail: {}", e),
+                        }
+                        continue;
+                    }
+                    let bots = self.bot_getter.clone().unwrap().borrow().clone();
+                    if let Some(bot) = bots.get(&event.get_self_id()) {
+                        self.handle_events(event, bot).await;
+                    }
                 }
             }
         }
-        get_block
     }
+}
 
-    async fn event_recv(mut self, mut event_receiver: EventReceiver) {
-        let mut receiver = self.action_sender.subscribe();
-        while let Ok(event) = event_receiver.recv().await {
-            if let Ok(action) = receiver.try_recv() {
-                self.handle_action(action)
-            }
+/// 单个 Event 的 span，覆盖 Matcher 匹配、Handler 运行及其调用的 Api
+fn event_span(event: &Event) -> Span {
+    let (post_type, group_id, user_id) = match event {
+        Event::Message(e) => ("message", e.get_group_id(), Some(e.get_user_id())),
+        Event::Notice(e) => ("notice", e.get_group_id(), Some(e.get_user_id())),
+        Event::Request(e) => ("request", e.get_group_id(), Some(e.get_user_id())),
+        Event::Meta(_) => ("meta_event", None, None),
+        Event::Nonebot(_) => ("nonebot", None, None),
+    };
+    let span = info_span!(
+        "event",
+        post_type,
+        bot_id = %event.get_self_id(),
+        group_id = Empty,
+        user_id = Empty
+    );
+    if let Some(group_id) = group_id {
+        span.record("group_id", group_id);
+    }
+    if let Some(user_id) = user_id {
+        span.record("user_id", user_id);
+    }
+    span
+}
 
-            let bots = self.bot_getter.clone().unwrap().borrow().clone();
-            if let Some(bot) = bots.get(&event.get_self_id()) {
-                self.handle_events(event, bot).await;
+/// 逐级匹配并运行 Handler，同级 Handler 并行运行
+///
+/// 仅在上一级未返回 `Flow::Stop` 时匹配下一级，临时 Matcher 仅在其 Handler 运行时移除；
+/// Handler 返回错误或 panic 时使用 `Matcher::fallback_flow`
+async fn dispatch<E>(
+    matcherb: MatchersBTreeMap<E>,
+    event: E,
+    bot: crate::bot::Bot,
+    switches: Switches,
+) where
+    E: MatcherEvent + Send + Sync + 'static + SelfId + GroupId,
+{
+    let group_id = event.get_group_id();
+    for matcherh in matcherb.into_values() {
+        let mut tasks = vec![];
+        for (name, matcher) in matcherh {
+            let switch_on = switches
+                .read()
+                .unwrap()
+                .is_enabled(&name, group_id.as_deref());
+            if !switch_on {
+                continue;
+            }
+            let mut matcher = matcher.build(bot.clone());
+            let Some(event) = matcher.match_(event.clone(), bot.config.clone()).await else {
+                continue;
+            };
+            if matcher.is_temp() {
+                // 并行分发的 Event 可能同时匹配同一临时 Matcher，仅运行一次
+                if !matcher.claim() {
+                    continue;
+                }
+                event!(Level::INFO, matcher = %name, "Remove matched temp matcher");
+                matcher.remove_matcher(&name).await;
+            }
+            event!(Level::INFO, matcher = %name, "Matched");
+            crate::metrics::metrics().matcher_hit(&name, matcher.is_temp());
+            let matcher = matcher.set_event(&event);
+            let handle = matcher.clone().handle(event);
+            let temp = matcher.is_temp();
+            let span = info_span!("matcher", matcher = %name);
+            let task = tokio::spawn(
+                async move {
+                    let start = std::time::Instant::now();
+                    let result = handle.await;
+                    crate::metrics::metrics().matcher_handled(&name, temp, start.elapsed());
+                    result
+                }
+                .instrument(span),
+            );
+            tasks.push((matcher, task));
+        }
+        let mut flow = Flow::Continue;
+        for (matcher, task) in tasks {
+            let result = match task.await {
+                Ok(Ok(flow)) => flow,
+                Ok(Err(e)) => {
+                    event!(
+                        Level::ERROR,
+                        matcher = %matcher.name,
+                        "Matcher handle error: {}",
+                        e
+                    );
+                    matcher.report_error(&e).await;
+                    matcher.fallback_flow()
+                }
+                Err(e) => {
+                    event!(
+                        Level::ERROR,
+                        matcher = %matcher.name,
+                        "Matcher panicked: {}",
+                        e
+                    );
+                    matcher.fallback_flow()
+                }
+            };
+            if result == Flow::Stop {
+                flow = Flow::Stop;
             }
         }
+        if flow == Flow::Stop {
+            break;
+        }
     }
 }
 
+#[async_trait::async_trait]
 impl Plugin for Matchers {
+    type Config = ();
+
     fn load(&self, event_receiver: EventReceiver, bot_getter: BotGetter) -> JoinHandle<()> {
         let mut matchers = self.clone();
         matchers.bot_getter = Some(bot_getter.clone());
         init_matchers(&matchers);
-        tokio::spawn(matchers.event_recv(event_receiver))
+        matchers
+            .switches
+            .write()
+            .unwrap()
+            .load(&matchers.data_path());
+        matchers.publish();
+        let action_receiver = matchers.action_sender.subscribe();
+        tokio::spawn(matchers.event_recv(event_receiver, action_receiver))
+    }
+
+    fn set_state(&mut self, state: StateMap) {
+        self.set_shared_state(state);
+    }
+
+    /// 从 `MatcherTable` 移除此 Matchers 的 Matcher
+    async fn shutdown(&self) {
+        if let Some(table) = self.state.get::<MatcherTable>() {
+            table.remove(&self.id);
+        }
     }
 
     fn plugin_info(&self) -> crate::plugin::PluginInfo {
@@ -158,6 +331,41 @@ impl Plugin for Matchers {
     }
 }
 
+impl Matchers {
+    /// 向共享状态中的 `MatcherTable` 更新 Matcher 列表
+    ///
+    /// 临时 Matcher 生命周期短且名称不复用，不在表中列出，也无法开关
+    pub(crate) fn publish(&self) {
+        fn infos<E>(matcherb: &MatchersBTreeMap<E>, event: &'static str) -> Vec<MatcherInfo>
+        where
+            E: Clone,
+        {
+            matcherb
+                .values()
+                .flat_map(|matcherh| matcherh.values())
+                .filter(|matcher| !matcher.temp)
+                .map(|matcher| MatcherInfo {
+                    name: matcher.name.clone(),
+                    event,
+                    priority: matcher.priority,
+                    stop_on_error: matcher.stop_on_error,
+                    disable: matcher.disable,
+                    switch: Default::default(),
+                })
+                .collect()
+        }
+
+        let Some(table) = self.state.get::<MatcherTable>() else {
+            return;
+        };
+        let mut all = infos(&self.message, "message");
+        all.extend(infos(&self.notice, "notice"));
+        all.extend(infos(&self.request, "request"));
+        all.extend(infos(&self.meta, "meta_event"));
+        table.publish(self.id, all, self.switches.clone());
+    }
+}
+
 fn log_load_matchers(matchers: &Matchers) {
     log_matcherb(&matchers.message);
     log_matcherb(&matchers.notice);
@@ -174,7 +382,7 @@ where
     }
     for matcherh in matcherb.values() {
         for name in matcherh.keys() {
-            event!(Level::INFO, "Matcher {} is Loaded", name.blue());
+            event!(Level::INFO, matcher = %name, "Matcher is Loaded");
         }
     }
 }
@@ -195,9 +403,108 @@ where
     }
     for matcherh in matcherb.values() {
         for matcher in matcherh.values() {
-            if matcher.init(&matchers.get_plugin_data_path()).is_err() {
-                event!(Level::ERROR, "Matcher {} init error.", matcher.name.red());
+            if matcher.init(&matchers.data_path()).is_err() {
+                event!(Level::ERROR, matcher = %matcher.name, "Matcher init error.");
             }
         }
     }
 }
+
+#[tokio::test]
+async fn disable_matcher_test() {
+    use crate::builtin::matcher::prelude::*;
+    use crate::testing::{MessageBuilder, TestBot};
+
+    async fn ping(reply: Reply) {
+        reply.text("pong").await;
+    }
+
+    let mut matchers = Matchers::new_empty();
+    matchers.add_message_matcher(Matcher::new(
+        "Ping",
+        FnHandler::new(ping).command(&["ping"]),
+    ));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    let switch = |disable| {
+        Event::Nonebot(NoneBotEvent::DisableMatcher {
+            name: "Ping".to_string(),
+            group_id: Some("disable_matcher_test".to_string()),
+            disable,
+        })
+    };
+    bot.send(switch(true));
+    bot.send_message(MessageBuilder::group("disable_matcher_test", "1", "ping").build());
+    bot.assert_no_api().await;
+    bot.send(switch(false));
+    bot.send_message(MessageBuilder::group("disable_matcher_test", "1", "ping").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("pong"));
+}
+
+#[tokio::test]
+async fn dispatch_test() {
+    use crate::builtin::matcher::prelude::*;
+    use crate::testing::{MessageBuilder, TestBot};
+
+    struct Ask;
+
+    #[async_trait]
+    impl Handler<MessageEvent> for Ask {
+        on_command!(MessageEvent, "ask");
+        async fn handle(&self, _: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+            let answer = matcher.session().prompt_text(Some("?")).await?;
+            matcher.send_text(&format!("answer {}", answer)).await;
+            Ok(Flow::Stop)
+        }
+    }
+
+    struct Stop;
+
+    #[async_trait]
+    impl Handler<MessageEvent> for Stop {
+        on_command!(MessageEvent, "stop");
+        async fn handle(&self, _: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+            matcher.send_text("stopped").await;
+            Ok(Flow::Stop)
+        }
+    }
+
+    let mut matchers = Matchers::new_empty();
+    matchers
+        .add_message_matcher(Matcher::new("Ask", Ask))
+        .add_message_matcher(Matcher::new("Stop", Stop).set_priority(-1));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::private("1", "ask").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("?"));
+    // 上一级 Stop 时，低一级的会话临时 Matcher 不会被移除
+    bot.send_message(MessageBuilder::private("1", "stop").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("stopped"));
+    bot.send_message(MessageBuilder::private("1", "42").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("answer 42"));
+    bot.assert_no_api().await;
+}
+
+#[tokio::test]
+async fn matcher_table_test() {
+    use crate::testing::TestBot;
+
+    let mut matchers = Matchers::new_empty();
+    let mut temp = crate::builtin::echo::echo();
+    temp.name = "Temp".to_string();
+    matchers
+        .add_message_matcher(crate::builtin::echo::echo())
+        .add_message_matcher(temp.set_temp(true));
+    let bot = TestBot::new();
+    matchers.set_state(bot.state().clone());
+    let table = bot.state().get::<MatcherTable>().unwrap();
+
+    matchers.publish();
+    assert!(table.get("Echo").is_some());
+    assert!(table.get("Temp").is_none());
+    assert_eq!(table.all().len(), 1);
+    matchers.shutdown().await;
+    assert!(table.all().is_empty());
+}
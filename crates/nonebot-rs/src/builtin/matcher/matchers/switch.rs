@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tracing::{event, Level};
+
+/// Matcher 开关持久化文件名
+pub static SWITCH_FILE: &str = "switch.json";
+
+/// Matchers 共享的 Matcher 开关表
+pub type Switches = Arc<RwLock<MatcherSwitches>>;
+
+/// 单个 Matcher 的开关设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatcherSwitch {
+    /// superuser 设置的默认开关，为 None 时默认启用
+    #[serde(default)]
+    pub default: Option<bool>,
+    /// 各群开关，优先于默认开关
+    #[serde(default)]
+    pub groups: HashMap<String, bool>,
+}
+
+impl MatcherSwitch {
+    /// 判定在指定群是否启用
+    pub fn is_enabled(&self, group_id: Option<&str>) -> bool {
+        if let Some(enable) = group_id.and_then(|g| self.groups.get(g)) {
+            return *enable;
+        }
+        self.default.unwrap_or(true)
+    }
+}
+
+/// Matcher 开关表
+///
+/// 仅在 `Matcher.disable` 为 false 时生效，不会覆盖代码中对 Matcher 的全局禁用
+#[derive(Debug, Default)]
+pub struct MatcherSwitches {
+    /// 持久化文件路径，为 None 时不持久化
+    path: Option<PathBuf>,
+    /// 已注册的 Matcher 名称
+    names: HashSet<String>,
+    /// 各 Matcher 开关设置
+    switches: HashMap<String, MatcherSwitch>,
+}
+
+impl MatcherSwitches {
+    /// 记录已注册的 Matcher 名称
+    pub fn register(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+    }
+
+    /// 移除已注册的 Matcher 名称，保留其开关设置
+    pub fn unregister(&mut self, name: &str) {
+        self.names.remove(name);
+    }
+
+    /// 是否存在该名称的 Matcher
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// 从 Matchers 数据目录读取开关设置
+    pub fn load(&mut self, data_path: &Path) {
+        let path = data_path.join(SWITCH_FILE);
+        if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+            {
+                Ok(switches) => self.switches = switches,
+                Err(e) => event!(Level::ERROR, "Load matcher switch file fail: {}", e),
+            }
+        }
+        self.path = Some(path);
+    }
+
+    /// 序列化开关设置，返回持久化文件路径与内容，不持久化时返回 None
+    pub(crate) fn snapshot(&self) -> serde_json::Result<Option<(PathBuf, String)>> {
+        match &self.path {
+            Some(path) => Ok(Some((
+                path.clone(),
+                serde_json::to_string_pretty(&self.switches)?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// 判定 Matcher 在指定群是否启用
+    pub fn is_enabled(&self, name: &str, group_id: Option<&str>) -> bool {
+        self.switches
+            .get(name)
+            .map_or(true, |switch| switch.is_enabled(group_id))
+    }
+
+    /// 设置 Matcher 开关，group_id 为 None 时设置默认开关，需经 `snapshot` 持久化
+    ///
+    /// Matcher 不存在时返回 false
+    pub fn set(&mut self, name: &str, group_id: Option<&str>, enable: bool) -> bool {
+        if !self.contains(name) {
+            return false;
+        }
+        let switch = self.switches.entry(name.to_string()).or_default();
+        match group_id {
+            Some(group_id) => {
+                switch.groups.insert(group_id.to_string(), enable);
+            }
+            None => switch.default = Some(enable),
+        }
+        true
+    }
+
+    /// 获取 Matcher 开关设置
+    pub fn get(&self, name: &str) -> Option<&MatcherSwitch> {
+        self.switches.get(name)
+    }
+}
+
+/// 设置 Matcher 开关并持久化，Matcher 不存在时返回 false
+///
+/// 持有锁时仅序列化快照，释放锁后写入文件
+pub(crate) async fn set_and_save(
+    switches: &Switches,
+    name: &str,
+    group_id: Option<&str>,
+    enable: bool,
+) -> std::io::Result<bool> {
+    let snapshot = {
+        let mut switches = switches.write().unwrap();
+        if !switches.set(name, group_id, enable) {
+            return Ok(false);
+        }
+        switches.snapshot()?
+    };
+    if let Some((path, contents)) = snapshot {
+        tokio::task::spawn_blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+    }
+    Ok(true)
+}
+
+#[test]
+fn switch_test() {
+    let mut switches = MatcherSwitches::default();
+    switches.register("Echo");
+    assert!(!switches.set("Unknown", None, false));
+    assert!(switches.set("Echo", None, false));
+    assert!(switches.set("Echo", Some("101"), true));
+    assert!(switches.is_enabled("Echo", Some("101")));
+    assert!(!switches.is_enabled("Echo", Some("102")));
+    assert!(!switches.is_enabled("Echo", None));
+    assert!(switches.is_enabled("Unknown", None));
+}
@@ -0,0 +1,68 @@
+use super::{MatcherSwitch, Switches};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Matcher 信息
+#[derive(Debug, Clone, Serialize)]
+pub struct MatcherInfo {
+    pub name: String,
+    /// 匹配的 Event 类型
+    pub event: &'static str,
+    pub priority: i8,
+    /// Handler 出错时是否阻止事件向下一级传递
+    pub stop_on_error: bool,
+    /// 代码中全局禁用
+    pub disable: bool,
+    /// 分群开关设置
+    pub switch: MatcherSwitch,
+}
+
+/// 所有 Matchers 中的 Matcher
+///
+/// 存放于 Nonebot 共享状态，可通过 `State<MatcherTable>` 获取
+#[derive(Debug, Default)]
+pub struct MatcherTable {
+    /// 以 Matchers 区分，开关设置读取时获取
+    inner: RwLock<HashMap<Uuid, (Vec<MatcherInfo>, Switches)>>,
+}
+
+impl MatcherTable {
+    /// 获取所有 Matcher，按优先级与名称排序
+    pub fn all(&self) -> Vec<MatcherInfo> {
+        let mut all: Vec<MatcherInfo> = self
+            .inner
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|(matchers, switches)| {
+                let switches = switches.read().unwrap();
+                matchers
+                    .iter()
+                    .map(|info| MatcherInfo {
+                        switch: switches.get(&info.name).cloned().unwrap_or_default(),
+                        ..info.clone()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        all.sort_by(|a, b| (a.priority, &a.name).cmp(&(b.priority, &b.name)));
+        all
+    }
+
+    /// 获取指定名称的 Matcher
+    pub fn get(&self, name: &str) -> Option<MatcherInfo> {
+        self.all().into_iter().find(|info| info.name == name)
+    }
+
+    /// 替换一个 Matchers 的 Matcher 列表
+    pub(crate) fn publish(&self, id: Uuid, matchers: Vec<MatcherInfo>, switches: Switches) {
+        self.inner.write().unwrap().insert(id, (matchers, switches));
+    }
+
+    /// 移除一个 Matchers 的 Matcher 列表，在 Matchers 关闭时调用
+    pub(crate) fn remove(&self, id: &Uuid) {
+        self.inner.write().unwrap().remove(id);
+    }
+}
@@ -1,8 +1,6 @@
+use super::session::Session;
 use super::{build_temp_message_event_matcher, Handler, Matcher};
 use crate::event::MessageEvent;
-use crate::ApiChannelItem;
-use async_trait::async_trait;
-use colored::*;
 use tracing::{event, Level};
 
 impl Matcher<MessageEvent> {
@@ -42,80 +40,20 @@ impl Matcher<MessageEvent> {
             }
         }
 
-        /// 临时 Matcher 的 Handler struct
-        struct Temp;
-
-        #[async_trait]
-        impl Handler<MessageEvent> for Temp {
-            crate::on_match_all!();
-            async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) {
-                matcher
-                    .bot
-                    .clone()
-                    .unwrap()
-                    .api_sender
-                    .send(ApiChannelItem::MessageEvent(event))
-                    .await
-                    .unwrap();
-            }
-
-            // timeout 后调用，通知接受端 Timeout
-            fn timeout_drop(&self, matcher: &Matcher<MessageEvent>) {
-                let sender = matcher.bot.clone().unwrap().api_sender;
-                tokio::spawn(async move { sender.send(ApiChannelItem::TimeOut).await.unwrap() });
-            }
-        }
-
-        // 搭建临时通道接受 MessageEvent
-        let (sender, mut receiver) = tokio::sync::mpsc::channel::<ApiChannelItem>(4);
-        let event = self.event.clone().unwrap();
-        // 根据提供的 event Handler 构建仅指向当先通话的 Temp Matcher
-        let mut m = build_temp_message_event_matcher(&event, Temp);
-        // 使用临时通道构建专用 Bot
-        let bot = crate::bot::Bot::new(
-            "Temp".to_string(),
-            crate::config::BotConfig::default(),
-            sender,
-            self.bot.clone().unwrap().action_sender.clone(),
-            self.bot.clone().unwrap().api_resp_watcher.clone(),
-        );
-        // 绑定专用 Bot
-        m.bot = Some(bot);
-        self.set_message_matcher(m).await;
-
-        // Temp Matcher 已就绪，发送提示信息
-        if let Some(msg) = msg {
-            self.send_text(msg).await;
-        }
-
-        // 等待接收 MessageEvent
-        while let Some(data) = receiver.recv().await {
-            match data {
-                ApiChannelItem::MessageEvent(event) => {
-                    let msg = crate::utils::remove_space(event.get_raw_message());
-                    if msg.is_empty() {
-                        return None;
-                    } else {
-                        return Some(msg);
-                    }
-                }
-                ApiChannelItem::TimeOut => {
-                    event!(Level::DEBUG, "Temp Matcher TimeOut");
-                    return None;
-                }
-                // 中转 temp Matcher 的 Remove Action
-                // ApiChannelItem::Action(action) => self.set(action).await,
-                _ => {
-                    event!(
-                        Level::WARN,
-                        "{}",
-                        "Temp Matcher接受端接收到错误Api或Action消息".bright_red()
-                    );
-                } // 忽视 event 该 receiver 永不应该收到 event
-            }
+        let event = self.session().prompt(msg).await.ok()?;
+        let msg = crate::utils::remove_space(event.get_raw_message());
+        if msg.is_empty() {
+            None
+        } else {
+            Some(msg)
         }
+    }
 
-        None
+    /// 开启多轮会话
+    ///
+    /// 会话仅匹配当前 event 的发送者（群消息同时限定当前群），默认每轮 30s 超时
+    pub fn session(&self) -> Session {
+        Session::new(self.clone())
     }
 
     /// 发送 Vec<Message> 消息
@@ -125,8 +63,8 @@ impl Matcher<MessageEvent> {
         } else {
             event!(
                 Level::ERROR,
-                "{}",
-                "Sending msg with unbuilt matcher!".red()
+                matcher = %self.name,
+                "Sending msg with unbuilt matcher!"
             );
         }
     }
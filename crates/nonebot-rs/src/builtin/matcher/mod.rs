@@ -1,10 +1,11 @@
 use crate::config::BotConfig;
-use crate::event::{MessageEvent, SelfId};
+use crate::event::{MessageEvent, MetaEvent, NoticeEvent, RequestEvent, SelfId};
 use crate::utils::timestamp;
 use crate::Action;
 use async_trait::async_trait;
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{event, Level};
@@ -12,19 +13,40 @@ use tracing::{event, Level};
 mod action;
 #[doc(hidden)]
 pub mod api;
+/// 基于参数提取的 handler 函数
+pub mod extract;
 #[doc(hidden)]
 pub mod matchers;
 #[doc(hidden)]
 pub mod message_event_matcher;
 /// Preludo for Matcher
 pub mod prelude;
+/// 多轮会话
+pub mod session;
 #[doc(hidden)]
 pub mod set_get;
+#[doc(hidden)]
+pub mod wait;
+
+pub use action::MatcherEvent;
 
 /// rule 函数类型
 pub type Rule<E> = Arc<dyn Fn(&E, &BotConfig) -> bool + Send + Sync>;
 /// permatcher 函数类型
 pub type PreMatcher<E> = fn(&mut E, BotConfig) -> bool;
+/// Handler 错误类型
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+/// Handler 返回类型
+pub type HandlerResult = Result<Flow, HandlerError>;
+
+/// Handler 处理完成后事件的传递方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// 阻止事件向下一级 priority 传递
+    Stop,
+    /// 事件继续向下一级 priority 传递
+    Continue,
+}
 
 /// 单个匹配器，参与匹配的最小单元
 ///
@@ -40,14 +62,22 @@ where
     pub bot: Option<crate::bot::Bot>,
     /// Matchers Action Sender
     action_sender: Option<matchers::ActionSender>,
+    /// Matchers 共享的 Matcher 开关表
+    switches: Option<matchers::Switches>,
+    /// Matchers 共享状态
+    state: Option<crate::state::StateMap>,
     /// Matcher 的匹配优先级
     pub priority: i8,
     /// 前处理函数组，获取 &mut event
     pre_matchers: Vec<Arc<PreMatcher<E>>>,
     /// rule 组
     rules: Vec<Rule<E>>,
-    /// 是否阻止事件向下一级传递
-    pub block: bool,
+    /// Handler 返回错误或 panic 时是否阻止事件向下一级传递
+    ///
+    /// 正常返回时事件传递由 Handler 返回的 `Flow` 决定（原 `block` 字段）
+    pub stop_on_error: bool,
+    /// Handler 返回错误时是否向用户发送错误信息（仅 MessageEvent）
+    pub error_reply: bool,
     /// Matcher 接口函数与可配置项结构体
     handler: Arc<RwLock<dyn Handler<E> + Sync + Send>>,
     /// 是否被禁用
@@ -56,6 +86,8 @@ where
     pub temp: bool,
     /// 过期时间戳
     pub timeout: Option<i64>,
+    /// 临时 Matcher 是否已被某一 Event 匹配并运行
+    claimed: Arc<AtomicBool>,
     base_data_path: Option<String>,
 
     #[doc(hidden)]
@@ -71,7 +103,8 @@ where
         f.debug_struct("Matcher")
             .field("name", &self.name)
             .field("priority", &self.priority)
-            .field("block", &self.block)
+            .field("stop_on_error", &self.stop_on_error)
+            .field("error_reply", &self.error_reply)
             .field("disable", &self.disable)
             .field("temp", &self.temp)
             .field("timeout", &self.timeout)
@@ -94,8 +127,8 @@ where
     fn timeout_drop(&self, _: &Matcher<E>) {}
     /// 匹配函数
     fn match_(&self, event: &mut E) -> bool;
-    /// 处理函数
-    async fn handle(&self, event: E, matcher: Matcher<E>);
+    /// 处理函数，返回值决定事件是否继续向下一级 priority 传递
+    async fn handle(&self, event: E, matcher: Matcher<E>) -> HandlerResult;
 }
 
 impl<E> Matcher<E>
@@ -111,14 +144,18 @@ where
             name: name.to_string(),
             bot: None,
             action_sender: None,
+            switches: None,
+            state: None,
             priority: 1,
             pre_matchers: vec![],
             rules: vec![],
-            block: true,
+            stop_on_error: true,
+            error_reply: false,
             handler: Arc::new(RwLock::new(handler)),
             disable: false,
             temp: false,
             timeout: None,
+            claimed: Arc::new(AtomicBool::new(false)),
 
             event: None,
             base_data_path: None,
@@ -167,49 +204,75 @@ where
     }
 
     #[doc(hidden)]
-    pub async fn match_(
-        &self,
-        event: E,
-        config: BotConfig,
-        matchers: &mut matchers::Matchers,
-    ) -> bool
+    pub async fn match_(&self, event: E, config: BotConfig) -> Option<E>
     where
         E: Send + 'static + SelfId,
     {
-        // Matcher 处理流程，匹配成功返回 true 并行处理 handler
+        // Matcher 匹配流程，匹配成功返回经 pre_matcher 与 match_ 处理后的 event
         let mut event = event.clone();
-        if let Some(timeout) = self.timeout {
-            if timestamp() > timeout {
-                matchers.remove_matcher(&self.name);
-                {
-                    let handler = self.handler.read().await;
-                    handler.timeout_drop(self);
-                }
-                return false;
-            }
-        }
-        if self.disable {
-            return false;
+        if self.is_expired() || self.disable {
+            return None;
         }
         if !self.pre_matcher_handle(&mut event, config.clone()) {
-            return false;
+            return None;
         }
         if !self.check_rules(&event, &config) {
-            return false;
+            return None;
         }
-        {
-            let handler = self.handler.read().await;
-            if !handler.match_(&mut event) {
-                return false;
-            }
-            let matcher = self.clone().set_event(&event);
-            let handler = self.handler.clone();
-            tokio::spawn(async move {
-                let handler = handler.read().await;
-                handler.handle(event, matcher).await
-            });
+        let handler = self.handler.read().await;
+        if !handler.match_(&mut event) {
+            return None;
+        }
+        Some(event)
+    }
+
+    /// 是否已超过过期时间戳
+    pub fn is_expired(&self) -> bool {
+        self.timeout.is_some_and(|timeout| timestamp() > timeout)
+    }
+
+    /// 调用 Handler 的 timeout drop 函数
+    pub(crate) async fn drop_timeout(&self) {
+        let handler = self.handler.read().await;
+        handler.timeout_drop(self);
+    }
+
+    /// 占用临时 Matcher，同一临时 Matcher 仅有首次调用返回 true
+    pub(crate) fn claim(&self) -> bool {
+        !self.claimed.swap(true, Ordering::SeqCst)
+    }
+
+    #[doc(hidden)]
+    pub async fn handle(self, event: E) -> HandlerResult {
+        let handler = self.handler.clone();
+        let handler = handler.read().await;
+        handler.handle(event, self).await
+    }
+
+    /// Handler 返回错误时的事件传递方式
+    pub fn fallback_flow(&self) -> Flow {
+        if self.stop_on_error {
+            Flow::Stop
+        } else {
+            Flow::Continue
+        }
+    }
+
+    #[doc(hidden)]
+    pub async fn report_error(&self, error: &HandlerError)
+    where
+        E: MatcherEvent,
+    {
+        if !self.error_reply {
+            return;
+        }
+        let event = self.event.as_ref().and_then(MatcherEvent::as_message_event);
+        if let (Some(bot), Some(event)) = (&self.bot, event) {
+            let msg = crate::message::Message::Text {
+                text: format!("{} 处理出错：{}", self.name, error),
+            };
+            bot.send_by_message_event(event, vec![msg]).await;
         }
-        true
     }
 
     /// 发送 nbrs 内部设置 Action
@@ -221,8 +284,41 @@ where
 
     /// 向 Matchers 添加 Matcher<MessageEvent>
     pub async fn set_message_matcher(&self, matcher: Matcher<MessageEvent>) {
-        let action = action::MatchersAction::AddMessageEventMatcher {
-            message_event_matcher: matcher,
+        self.set_matcher(matcher).await;
+    }
+
+    /// 向 Matchers 添加 Matcher<NoticeEvent>
+    pub async fn set_notice_matcher(&self, matcher: Matcher<NoticeEvent>) {
+        self.set_matcher(matcher).await;
+    }
+
+    /// 向 Matchers 添加 Matcher<RequestEvent>
+    pub async fn set_request_matcher(&self, matcher: Matcher<RequestEvent>) {
+        self.set_matcher(matcher).await;
+    }
+
+    /// 向 Matchers 添加 Matcher<MetaEvent>
+    pub async fn set_meta_matcher(&self, matcher: Matcher<MetaEvent>) {
+        self.set_matcher(matcher).await;
+    }
+
+    /// 向 Matchers 添加任意 Event 类型的 Matcher
+    pub async fn set_matcher<T>(&self, matcher: Matcher<T>)
+    where
+        T: MatcherEvent,
+    {
+        let action = T::add_matcher_action(matcher);
+        if let Some(action_sender) = &self.action_sender {
+            action_sender.send(action).unwrap();
+        } else {
+            event!(Level::WARN, "Action Sender not init.")
+        }
+    }
+
+    /// 从 Matchers 移除 Matcher
+    pub async fn remove_matcher(&self, name: &str) {
+        let action = action::MatchersAction::RemoveMatcher {
+            matcher_name: name.to_string(),
         };
         if let Some(action_sender) = &self.action_sender {
             action_sender.send(action).unwrap();
@@ -230,6 +326,37 @@ where
             event!(Level::WARN, "Action Sender not init.")
         }
     }
+
+    /// 设置 Matchers 中 Matcher 的分群开关并持久化，group_id 为 None 时设置默认开关
+    ///
+    /// Matcher 不存在时返回 false
+    pub async fn set_switch(
+        &self,
+        name: &str,
+        group_id: Option<&str>,
+        enable: bool,
+    ) -> std::io::Result<bool> {
+        match &self.switches {
+            Some(switches) => matchers::set_and_save(switches, name, group_id, enable).await,
+            None => {
+                event!(Level::WARN, "Switches not init.");
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl<E> Matcher<E>
+where
+    E: Clone,
+{
+    /// 获取 Matchers 共享状态
+    pub fn get_state<T>(&self) -> Option<crate::state::State<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.state.as_ref()?.get::<T>()
+    }
 }
 
 /// 构建 timeout 为 30s 的临时 Matcher<MessageEvent>
@@ -237,6 +364,18 @@ pub fn build_temp_message_event_matcher<H>(
     event: &MessageEvent,
     handler: H,
 ) -> Matcher<MessageEvent>
+where
+    H: Handler<MessageEvent> + Send + Sync + 'static,
+{
+    build_temp_message_event_matcher_with_timeout(event, handler, timestamp() + 30)
+}
+
+/// 构建指定过期时间戳的临时 Matcher<MessageEvent>
+pub fn build_temp_message_event_matcher_with_timeout<H>(
+    event: &MessageEvent,
+    handler: H,
+    timeout: i64,
+) -> Matcher<MessageEvent>
 where
     H: Handler<MessageEvent> + Send + Sync + 'static,
 {
@@ -257,7 +396,50 @@ where
     } else {
         m.add_rule(crate::builtin::rules::is_private_message_event());
     }
-    m.set_priority(0)
-        .set_temp(true)
-        .set_timeout(timestamp() + 30)
+    m.set_priority(0).set_temp(true).set_timeout(timeout)
+}
+
+#[tokio::test]
+async fn report_error_test() {
+    use crate::builtin::matcher::prelude::*;
+    use crate::testing::{MessageBuilder, TestBot};
+
+    struct Fail;
+
+    #[async_trait]
+    impl Handler<MessageEvent> for Fail {
+        on_command!(MessageEvent, "fail");
+        async fn handle(&self, _: MessageEvent, _: Matcher<MessageEvent>) -> HandlerResult {
+            Err("boom".into())
+        }
+    }
+
+    struct Fallback;
+
+    #[async_trait]
+    impl Handler<MessageEvent> for Fallback {
+        on_match_all!();
+        async fn handle(&self, _: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+            matcher.send_text("fallback").await;
+            Ok(Flow::Stop)
+        }
+    }
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers
+        .add_message_matcher(
+            Matcher::new("Fail", Fail)
+                .set_error_reply(true)
+                .set_stop_on_error(false),
+        )
+        .add_message_matcher(Matcher::new("Fallback", Fallback).set_priority(2));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::private("1", "fail").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("Fail 处理出错：boom")
+    );
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("fallback"));
 }
@@ -1,7 +1,12 @@
-pub use super::{Handler, Matcher};
+pub use super::extract::{
+    Args, FnHandler, FromEvent, GroupId, MessageChain, RawMessage, Reply, Sender,
+};
+pub use super::session::{Session, SessionError};
+pub use super::{Flow, Handler, HandlerError, HandlerResult, Matcher};
 pub use crate::async_trait;
 pub use crate::builtin::*;
-pub use crate::event::{Event, MessageEvent, SelfId, UserId};
+pub use crate::event::{Event, MessageEvent, MetaEvent, NoticeEvent, RequestEvent, SelfId, UserId};
 pub use crate::message::Message;
+pub use crate::state::State;
 pub use crate::{on_command, on_match_all, on_start_with};
 pub use serde_json::Value;
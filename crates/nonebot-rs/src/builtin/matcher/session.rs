@@ -0,0 +1,257 @@
+use super::{build_temp_message_event_matcher_with_timeout, Flow, Handler, HandlerResult, Matcher};
+use crate::event::MessageEvent;
+use crate::message::Message;
+use crate::utils::{remove_space, timestamp};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::{event, Level};
+
+/// 会话默认超时时间（秒）
+pub const DEFAULT_SESSION_TIMEOUT: i64 = 30;
+
+/// 会话等待失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// 等待超时
+    Timeout,
+    /// 用户发送了取消关键词
+    Cancelled,
+    /// 会话无法继续（Matcher 未绑定 event 或通道已关闭）
+    Closed,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Timeout => write!(f, "session timeout"),
+            SessionError::Cancelled => write!(f, "session cancelled"),
+            SessionError::Closed => write!(f, "session closed"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// 多轮会话
+///
+/// 由 `Matcher::session` 创建，每次 `prompt` 都会注册一个仅匹配当前会话用户的临时 Matcher
+pub struct Session {
+    /// 发起会话的 Matcher
+    matcher: Matcher<MessageEvent>,
+    /// 每轮等待超时时间（秒）
+    timeout: i64,
+    /// 取消关键词
+    cancel_keywords: Vec<String>,
+    /// 会话内状态
+    state: HashMap<String, Value>,
+    /// 最近一次收到的回复
+    last: Option<MessageEvent>,
+}
+
+/// 临时 Matcher 的 Handler，将匹配的 MessageEvent 转发回会话
+struct Relay {
+    sender: mpsc::Sender<MessageEvent>,
+}
+
+#[async_trait]
+impl Handler<MessageEvent> for Relay {
+    fn match_(&self, _: &mut MessageEvent) -> bool {
+        true
+    }
+
+    async fn handle(&self, event: MessageEvent, _: Matcher<MessageEvent>) -> HandlerResult {
+        self.sender.send(event).await.ok();
+        Ok(Flow::Stop)
+    }
+}
+
+impl Session {
+    /// 新建会话，通常使用 `Matcher::session` 创建
+    pub fn new(matcher: Matcher<MessageEvent>) -> Self {
+        Session {
+            matcher,
+            timeout: DEFAULT_SESSION_TIMEOUT,
+            cancel_keywords: vec![],
+            state: HashMap::new(),
+            last: None,
+        }
+    }
+
+    /// 设置每轮等待超时时间（秒）
+    pub fn timeout(mut self, timeout: i64) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置取消关键词，收到完全一致的消息时结束会话
+    pub fn cancel_keywords(mut self, keywords: &[&str]) -> Self {
+        self.cancel_keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// 发起会话的 Matcher
+    pub fn matcher(&self) -> &Matcher<MessageEvent> {
+        &self.matcher
+    }
+
+    /// 最近一次收到的回复
+    pub fn last(&self) -> Option<&MessageEvent> {
+        self.last.as_ref()
+    }
+
+    /// 发送提示信息（None 表示不发送）并等待用户的下一条消息
+    pub async fn prompt(&mut self, msg: Option<&str>) -> Result<MessageEvent, SessionError> {
+        let Some(event) = self.matcher.event.clone() else {
+            event!(Level::ERROR, "Prompt with unbuilt matcher!");
+            return Err(SessionError::Closed);
+        };
+
+        let (sender, mut receiver) = mpsc::channel(1);
+        let m = build_temp_message_event_matcher_with_timeout(
+            &event,
+            Relay { sender },
+            timestamp() + self.timeout,
+        );
+        let name = m.name.clone();
+        self.matcher.set_message_matcher(m).await;
+
+        if let Some(msg) = msg {
+            self.matcher.send_text(msg).await;
+        }
+
+        let duration = std::time::Duration::from_secs(self.timeout.max(0) as u64);
+        let reply = match tokio::time::timeout(duration, receiver.recv()).await {
+            Ok(Some(reply)) => reply,
+            Ok(None) => return Err(SessionError::Closed),
+            Err(_) => {
+                event!(Level::DEBUG, "Session {} timeout", name);
+                self.matcher.remove_matcher(&name).await;
+                return Err(SessionError::Timeout);
+            }
+        };
+
+        let raw_message = remove_space(reply.get_raw_message());
+        if self
+            .cancel_keywords
+            .iter()
+            .any(|k| k == raw_message.trim_end())
+        {
+            return Err(SessionError::Cancelled);
+        }
+        self.last = Some(reply.clone());
+        Ok(reply)
+    }
+
+    /// 发送提示信息并等待用户的下一条消息，返回消息段
+    pub async fn prompt_message(
+        &mut self,
+        msg: Option<&str>,
+    ) -> Result<Vec<Message>, SessionError> {
+        self.prompt(msg)
+            .await
+            .map(|event| event.get_message().clone())
+    }
+
+    /// 发送提示信息并等待用户的下一条消息，返回去除前方空格的文本
+    pub async fn prompt_text(&mut self, msg: Option<&str>) -> Result<String, SessionError> {
+        self.prompt(msg)
+            .await
+            .map(|event| remove_space(event.get_raw_message()))
+    }
+
+    /// 拒绝上一次回复，发送提示信息并重新等待
+    pub async fn reject(&mut self, msg: &str) -> Result<MessageEvent, SessionError> {
+        self.last = None;
+        self.prompt(Some(msg)).await
+    }
+
+    /// 写入会话状态
+    pub fn set<T: Serialize>(&mut self, key: &str, value: T) {
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.state.insert(key.to_string(), value);
+            }
+            Err(e) => event!(Level::WARN, "Session state {} serialize fail: {}", key, e),
+        }
+    }
+
+    /// 读取会话状态
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.state
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// 移除会话状态
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.state.remove(key)
+    }
+}
+
+#[tokio::test]
+async fn session_test() {
+    use super::prelude::*;
+    use crate::testing::{MessageBuilder, TestBot};
+
+    async fn age(matcher: Matcher<MessageEvent>) {
+        let mut session = matcher.session().timeout(1).cancel_keywords(&["取消"]);
+        let mut reply = session.prompt_text(Some("几岁？")).await;
+        loop {
+            match reply {
+                Ok(text) if text.parse::<u8>().is_ok() => {
+                    session.set("age", text);
+                    break;
+                }
+                Ok(_) => {
+                    reply = session
+                        .reject("请输入数字")
+                        .await
+                        .map(|e| e.get_raw_message().to_string())
+                }
+                Err(e) => {
+                    matcher.send_text(&e.to_string()).await;
+                    return;
+                }
+            }
+        }
+        let age: String = session.get("age").unwrap();
+        matcher.send_text(&format!("{} 岁", age)).await;
+    }
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers.add_message_matcher(Matcher::new("Age", FnHandler::new(age).command(&["age"])));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    // reject 后重新等待
+    bot.send_message(MessageBuilder::group("2", "1", "age").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("几岁？"));
+    bot.send_message(MessageBuilder::group("2", "1", "十八").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("请输入数字"));
+    bot.send_message(MessageBuilder::group("2", "1", "18").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("18 岁"));
+
+    // 取消关键词
+    bot.send_message(MessageBuilder::group("2", "1", "age").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("几岁？"));
+    bot.send_message(MessageBuilder::group("2", "1", "取消").build());
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("session cancelled")
+    );
+
+    // 超时后临时 Matcher 被移除，后续消息不再进入会话
+    bot.send_message(MessageBuilder::group("2", "1", "age").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("几岁？"));
+    bot.wait = std::time::Duration::from_secs(2);
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("session timeout")
+    );
+    bot.wait = crate::testing::DEFAULT_WAIT;
+    bot.send_message(MessageBuilder::group("2", "1", "18").build());
+    bot.assert_no_api().await;
+}
@@ -23,6 +23,18 @@ where
         self.action_sender = Some(action_sender);
     }
 
+    /// 为 Matcher 添加 Matchers 共享的开关表
+    /// 会在向 Matchers 添加时调用
+    pub fn set_switches(&mut self, switches: super::matchers::Switches) {
+        self.switches = Some(switches);
+    }
+
+    /// 为 Matcher 添加 Matchers 共享状态
+    /// 会在向 Matchers 添加时调用
+    pub fn set_state(&mut self, state: crate::state::StateMap) {
+        self.state = Some(state);
+    }
+
     /// 设置 priority
     pub fn set_priority(&mut self, priority: i8) -> Matcher<E> {
         self.priority = priority;
@@ -41,9 +53,21 @@ where
         self.clone()
     }
 
-    /// 设置是否阻塞消息向下一级 priority 传递
+    /// 设置 Handler 返回错误或 panic 时是否阻止事件向下一级 priority 传递
+    pub fn set_stop_on_error(&mut self, stop_on_error: bool) -> Matcher<E> {
+        self.stop_on_error = stop_on_error;
+        self.clone()
+    }
+
+    /// 同 `set_stop_on_error`，正常返回时事件传递由 Handler 返回的 `Flow` 决定
+    #[deprecated(note = "use `set_stop_on_error`, propagation is decided by the returned `Flow`")]
     pub fn set_block(&mut self, block: bool) -> Matcher<E> {
-        self.block = block;
+        self.set_stop_on_error(block)
+    }
+
+    /// 设置 Handler 返回错误时是否向用户发送错误信息
+    pub fn set_error_reply(&mut self, error_reply: bool) -> Matcher<E> {
+        self.error_reply = error_reply;
         self.clone()
     }
 
@@ -73,9 +97,10 @@ where
         self.clone()
     }
 
-    /// 返回 bolck
+    /// 同 `stop_on_error` 字段
+    #[deprecated(note = "use the `stop_on_error` field")]
     pub fn is_block(&self) -> bool {
-        self.block
+        self.stop_on_error
     }
 
     /// 判定是否为临时 Matcher
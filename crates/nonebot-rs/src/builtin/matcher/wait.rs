@@ -0,0 +1,200 @@
+use super::{Flow, Handler, HandlerResult, Matcher, MatcherEvent};
+use crate::event::SelfId;
+use crate::utils::timestamp;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{event, Level};
+
+/// wait_for 断言函数类型
+pub type Predicate<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// 临时 Matcher 的 Handler，将满足断言的 Event 转发回等待方
+struct Waiter<T> {
+    predicate: Predicate<T>,
+    sender: mpsc::Sender<T>,
+}
+
+#[async_trait]
+impl<T> Handler<T> for Waiter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn match_(&self, event: &mut T) -> bool {
+        (self.predicate)(event)
+    }
+
+    async fn handle(&self, event: T, _: Matcher<T>) -> HandlerResult {
+        self.sender.send(event).await.ok();
+        Ok(Flow::Continue)
+    }
+}
+
+impl<E> Matcher<E>
+where
+    E: Clone,
+{
+    /// 等待当前 Bot 收到满足 predicate 的 Event
+    ///
+    /// 超时（秒）或 Matcher 未绑定 Bot 时返回 None，等待中的 Event 不会阻止其他 Matcher 匹配
+    ///
+    /// ```ignore
+    /// let poke = matcher
+    ///     .wait_for::<NoticeEvent>(|n| n.sub_type.as_deref() == Some("poke"), 30)
+    ///     .await;
+    /// ```
+    pub async fn wait_for<T>(
+        &self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+        timeout: i64,
+    ) -> Option<T>
+    where
+        T: MatcherEvent + SelfId + Send + Sync + 'static,
+    {
+        let Some(bot) = &self.bot else {
+            event!(Level::ERROR, "Waiting event with unbuilt matcher!");
+            return None;
+        };
+
+        let (sender, mut receiver) = mpsc::channel(1);
+        let name = format!("{}-wait-{}", bot.bot_id, uuid::Uuid::new_v4());
+        let waiter = Waiter {
+            predicate: Arc::new(predicate),
+            sender,
+        };
+        let m = Matcher::new(&name, waiter)
+            .add_rule(crate::builtin::rules::is_bot(bot.bot_id.clone()))
+            .set_priority(0)
+            .set_stop_on_error(false)
+            .set_temp(true)
+            .set_timeout(timestamp() + timeout);
+        self.set_matcher(m).await;
+
+        let duration = std::time::Duration::from_secs(timeout.max(0) as u64);
+        match tokio::time::timeout(duration, receiver.recv()).await {
+            Ok(event) => event,
+            Err(_) => {
+                event!(Level::DEBUG, "Wait for {} timeout", name);
+                self.remove_matcher(&name).await;
+                None
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn wait_for_test() {
+    use crate::builtin::matcher::prelude::*;
+    use crate::testing::{MessageBuilder, TestBot, TEST_BOT_ID};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 断言调用次数，临时 Matcher 移除后不再增加
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    async fn poke(matcher: Matcher<MessageEvent>) {
+        let wait = matcher.wait_for::<NoticeEvent>(
+            |n| {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                n.sub_type.as_deref() == Some("poke")
+            },
+            1,
+        );
+        tokio::pin!(wait);
+        // 首次 poll 注册临时 Matcher 后才发送提示
+        let notice = tokio::select! {
+            biased;
+            notice = &mut wait => notice,
+            _ = matcher.send_text("等待中") => wait.await,
+        };
+        match notice {
+            Some(notice) => {
+                matcher
+                    .send_text(&format!("{} 戳了戳", notice.user_id))
+                    .await
+            }
+            None => matcher.send_text("超时").await,
+        }
+    }
+
+    /// 低优先级 Notice Matcher，用于确认 Event 继续向下传递
+    struct NoticeLog;
+
+    #[async_trait]
+    impl Handler<NoticeEvent> for NoticeLog {
+        fn match_(&self, _: &mut NoticeEvent) -> bool {
+            true
+        }
+
+        async fn handle(&self, event: NoticeEvent, matcher: Matcher<NoticeEvent>) -> HandlerResult {
+            let text = format!("notice {}", event.sub_type.unwrap_or_default());
+            let bot = matcher.bot.unwrap();
+            bot.send_private_msg(&event.user_id, vec![Message::Text { text }])
+                .await;
+            Ok(Flow::Continue)
+        }
+    }
+
+    let notice = |sub_type: &str| {
+        Event::Notice(NoticeEvent {
+            time: 0,
+            self_id: TEST_BOT_ID.to_string(),
+            notice_type: "notify".to_string(),
+            sub_type: Some(sub_type.to_string()),
+            group_id: Some("2".to_string()),
+            operator_id: None,
+            user_id: "3".to_string(),
+            file: None,
+            duration: None,
+            message_id: None,
+            target_id: None,
+            honor_type: None,
+        })
+    };
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers
+        .add_message_matcher(Matcher::new(
+            "Poke",
+            FnHandler::new(poke).command(&["poke"]),
+        ))
+        .add_notice_matcher(Matcher::new("NoticeLog", NoticeLog));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    // 不满足断言的 Event 被忽略，低优先级 Matcher 照常运行
+    bot.send_message(MessageBuilder::group("2", "1", "poke").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("等待中"));
+    bot.send(notice("group_upload"));
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("notice group_upload")
+    );
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+    // 满足断言的 Event 被返回，且仍向下传递
+    bot.send(notice("poke"));
+    let mut replies = vec![
+        bot.next_reply_text().await.unwrap(),
+        bot.next_reply_text().await.unwrap(),
+    ];
+    replies.sort();
+    assert_eq!(replies, ["3 戳了戳", "notice poke"]);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+    // 匹配后临时 Matcher 已移除
+    bot.send(notice("poke"));
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("notice poke"));
+    bot.assert_no_api().await;
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+    // 超时返回 None，临时 Matcher 同样被移除
+    bot.send_message(MessageBuilder::group("2", "1", "poke").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("等待中"));
+    bot.wait = std::time::Duration::from_secs(2);
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("超时"));
+    bot.wait = crate::testing::DEFAULT_WAIT;
+    bot.send(notice("poke"));
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("notice poke"));
+    bot.assert_no_api().await;
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
@@ -1,8 +1,12 @@
 #[doc(hidden)]
 pub mod macros;
 
+/// superuser 管理命令
+pub mod admin;
 /// Bot Status
 pub mod bot_status;
+/// 触发冷却
+pub mod cooldown;
 /// 内建 echo Matcher
 pub mod echo;
 /// 内建 logger
@@ -13,6 +17,8 @@ pub mod matcher;
 pub mod prematchers;
 /// 内建 rules
 pub mod rules;
+/// 内建 Matcher 分群开关
+pub mod switch;
 
 use tracing::{event, Level};
 
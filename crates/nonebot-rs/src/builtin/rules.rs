@@ -74,3 +74,36 @@ pub fn is_private_message_event() -> Rule<MessageEvent> {
     };
     Arc::new(is_private_message_event)
 }
+
+/// 判定 event 是否为群消息事件
+pub fn is_group_message_event() -> Rule<MessageEvent> {
+    let is_group_message_event = |event: &MessageEvent, _: &BotConfig| -> bool {
+        match event {
+            MessageEvent::Group(_) => true,
+            MessageEvent::Private(_) => false,
+        }
+    };
+    Arc::new(is_group_message_event)
+}
+
+/// 判定 sender 是否为群主或群管理员
+pub fn is_group_admin() -> Rule<MessageEvent> {
+    let is_group_admin = |event: &MessageEvent, _: &BotConfig| -> bool {
+        match event {
+            MessageEvent::Group(g) => g.sender.role == "owner" || g.sender.role == "admin",
+            MessageEvent::Private(_) => false,
+        }
+    };
+    Arc::new(is_group_admin)
+}
+
+/// 任一 rule 通过即通过
+pub fn any_of<E>(rules: Vec<Rule<E>>) -> Rule<E>
+where
+    E: 'static,
+{
+    let any_of = move |event: &E, config: &BotConfig| -> bool {
+        rules.iter().any(|rule| rule(event, config))
+    };
+    Arc::new(any_of)
+}
@@ -0,0 +1,174 @@
+use crate::builtin::matcher::matchers::MatcherTable;
+use crate::builtin::matcher::prelude::*;
+use crate::Action;
+
+/// 开关 Matcher 名称
+const SWITCH_NAME: &str = "MatcherSwitch";
+/// 管理命令 Matcher 名称前缀
+pub(crate) const ADMIN_PREFIX: &str = "Admin";
+/// 默认开关参数
+pub(crate) const DEFAULT_SCOPE: &str = "default";
+const USAGE: &str = "用法：enable|disable <Matcher> [群号|default]";
+
+#[derive(Debug)]
+struct Switch;
+
+#[async_trait]
+impl Handler<MessageEvent> for Switch {
+    fn match_(&self, event: &mut MessageEvent) -> bool {
+        matches!(
+            event.get_raw_message().split_whitespace().next(),
+            Some("enable" | "disable")
+        )
+    }
+
+    async fn handle(&self, event: MessageEvent, matcher: Matcher<MessageEvent>) -> HandlerResult {
+        let raw_message = event.get_raw_message().to_string();
+        let mut args = raw_message.split_whitespace();
+        let enable = match args.next() {
+            Some("enable") => true,
+            Some("disable") => false,
+            _ => return Ok(Flow::Continue),
+        };
+        let (Some(name), scope) = (args.next(), args.next()) else {
+            matcher.send_text(USAGE).await;
+            return Ok(Flow::Stop);
+        };
+        if let Err(reply) = check_target(&matcher, name) {
+            matcher.send_text(&reply).await;
+            return Ok(Flow::Stop);
+        }
+
+        let config = match &matcher.bot {
+            Some(bot) => bot.config.clone(),
+            None => Default::default(),
+        };
+        let superuser = rules::is_superuser()(&event, &config);
+        let group_id = match (scope, &event) {
+            (Some(DEFAULT_SCOPE), _) if superuser => None,
+            (Some(group_id), _) if superuser => Some(group_id.to_string()),
+            (None, MessageEvent::Group(g))
+                if superuser || rules::is_group_admin()(&event, &config) =>
+            {
+                Some(g.group_id.clone())
+            }
+            (None, MessageEvent::Private(_)) if superuser => {
+                matcher.send_text(USAGE).await;
+                return Ok(Flow::Stop);
+            }
+            _ => {
+                matcher.send_text("权限不足").await;
+                return Ok(Flow::Stop);
+            }
+        };
+
+        set_switch(&matcher, name, group_id, enable).await;
+        Ok(Flow::Stop)
+    }
+}
+
+/// 检查 Matcher 能否开关，不能时返回回复内容
+///
+/// 开关本身与 `Admin` 开头的管理命令 Matcher 不可开关，临时 Matcher 不在
+/// `MatcherTable` 中，视为未找到
+pub(crate) fn check_target(matcher: &Matcher<MessageEvent>, name: &str) -> Result<(), String> {
+    if name == SWITCH_NAME || name.starts_with(ADMIN_PREFIX) {
+        return Err(format!("不能开关 {}", name));
+    }
+    match matcher.get_state::<MatcherTable>() {
+        Some(table) if table.get(name).is_some() => Ok(()),
+        _ => Err(format!("未找到 {}", name)),
+    }
+}
+
+/// 经 `Action::DisableMatcher` 设置所有 Matchers 中的 Matcher 开关并回复，
+/// group_id 为 None 时设置默认开关
+pub(crate) async fn set_switch(
+    matcher: &Matcher<MessageEvent>,
+    name: &str,
+    group_id: Option<String>,
+    enable: bool,
+) {
+    let reply = format!(
+        "已在{}{} {}",
+        scope_text(&group_id),
+        state_text(enable),
+        name
+    );
+    matcher
+        .set(Action::DisableMatcher {
+            name: name.to_string(),
+            group_id,
+            disable: !enable,
+        })
+        .await;
+    matcher.send_text(&reply).await;
+}
+
+/// 开关范围描述
+pub(crate) fn scope_text(group_id: &Option<String>) -> String {
+    match group_id {
+        Some(group_id) => format!("群 {}", group_id),
+        None => "默认".to_string(),
+    }
+}
+
+/// 开关状态描述
+pub(crate) fn state_text(enable: bool) -> &'static str {
+    if enable {
+        "启用"
+    } else {
+        "禁用"
+    }
+}
+
+/// Matcher 分群开关 Matcher
+///
+/// 群管理员可开关本群 Matcher，superuser 可设置任意群及默认开关
+pub fn matcher_switch() -> Matcher<MessageEvent> {
+    Matcher::new(SWITCH_NAME, Switch).add_pre_matcher(prematchers::command_start())
+}
+
+#[tokio::test]
+async fn matcher_switch_test() {
+    use crate::testing::{MessageBuilder, TestBot};
+
+    async fn ping(matcher: Matcher<MessageEvent>) {
+        matcher.send_text("pong").await;
+    }
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers.add_message_matcher(matcher_switch());
+    matchers.add_message_matcher(Matcher::new(
+        "Ping",
+        FnHandler::new(ping).command(&["ping"]),
+    ));
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    // 仅匹配完整的命令
+    let admin = |text: &str| MessageBuilder::group("2", "1", text).role("admin").build();
+    bot.send_message(admin("disabled Ping"));
+    bot.assert_no_api().await;
+
+    bot.send_message(MessageBuilder::group("2", "1", "disable Ping").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("权限不足"));
+    bot.send_message(admin("disable Ping 3"));
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("权限不足"));
+    bot.send_message(admin("disable MatcherSwitch"));
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("不能开关 MatcherSwitch")
+    );
+
+    bot.send_message(admin("disable Ping"));
+    assert!(matches!(
+        bot.next_action().await,
+        Some(Action::DisableMatcher { name, group_id: Some(group_id), disable: true })
+            if name == "Ping" && group_id == "2"
+    ));
+    assert_eq!(
+        bot.next_reply_text().await.as_deref(),
+        Some("已在群 2禁用 Ping")
+    );
+}
@@ -1,11 +1,51 @@
-use colored::*;
+use crate::secret::Secret;
 use config::Config;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use tracing::{event, Level};
 
 /// nbrs 配置文件名
 pub static CONFIG_PATH: &str = "Nonebotrs.toml";
+/// 指定配置文件路径的环境变量
+pub static CONFIG_ENV: &str = "NBRS_CONFIG";
+/// 覆盖配置项的环境变量前缀
+pub static ENV_PREFIX: &str = "NBRS";
+/// 以 `,` 分隔解析为列表的环境变量配置项
+static ENV_LIST_KEYS: [&str; 3] = [
+    "global.superusers",
+    "global.nicknames",
+    "global.command_starts",
+];
+
+/// 配置读取错误
+#[derive(Debug)]
+pub enum ConfigError {
+    /// 读取配置文件失败
+    Io(PathBuf, std::io::Error),
+    /// 配置格式或类型错误
+    Parse(PathBuf, config::ConfigError),
+    /// 配置项不合法，(配置项, 原因)
+    Invalid(Vec<(String, String)>),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "read {} fail: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "parse {} fail: {}", path.display(), e),
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid config:")?;
+                for (key, message) in errors {
+                    write!(f, "\n  {}: {}", key, message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
 
 /// nbrs 配置项结构体
 #[derive(Serialize, Deserialize, Clone)]
@@ -16,6 +56,27 @@ pub struct NoneBotConfig {
     pub bots: Option<HashMap<String, BotConfig>>,
     /// 反向 WS 服务器设置
     pub ws_server: Option<WebSocketServerConfig>,
+    /// WebSocket 原始帧记录设置
+    #[serde(default)]
+    pub record: Option<RecordConfig>,
+    /// 回放记录文件设置
+    #[serde(default)]
+    pub replay: Option<ReplayConfig>,
+    /// Prometheus 指标服务设置
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+    /// 管理 HTTP Api 设置
+    #[serde(default)]
+    pub admin: Option<AdminConfig>,
+    /// Plugin 配置，以 Plugin 名称为键
+    #[serde(default)]
+    pub plugins: toml::Table,
+    /// 配置文件路径
+    #[serde(skip)]
+    path: PathBuf,
+    /// 类型为 `Secret` 的 Plugin 配置项路径（小写），读取 Plugin 配置时记录
+    #[serde(skip)]
+    plugin_secrets: BTreeSet<String>,
 }
 
 impl std::fmt::Debug for NoneBotConfig {
@@ -23,6 +84,11 @@ impl std::fmt::Debug for NoneBotConfig {
         f.debug_struct("NoneBotConfig")
             .field("Global", &self.global)
             .field("Bots", &self.bots)
+            .field("Record", &self.record)
+            .field("Replay", &self.replay)
+            .field("Metrics", &self.metrics)
+            .field("Admin", &self.admin)
+            .field("Plugins", &self.plugins)
             .finish()
     }
 }
@@ -34,10 +100,120 @@ pub struct WebSocketServerConfig {
     pub host: std::net::Ipv4Addr,
     /// Port
     pub port: u16,
-    /// Onebot authorization
+    /// Onebot authorization，支持 `env:`、`file:` 引用
     #[serde(alias = "access-token")]
     #[serde(default)]
-    access_token: String,
+    access_token: Secret,
+}
+
+fn default_record_dir() -> std::path::PathBuf {
+    std::path::PathBuf::from("data/record")
+}
+
+fn default_record_max_size() -> u64 {
+    8 * 1024 * 1024
+}
+
+fn default_record_max_files() -> usize {
+    5
+}
+
+fn default_replay_speed() -> f64 {
+    1.0
+}
+
+fn default_local_host() -> std::net::Ipv4Addr {
+    std::net::Ipv4Addr::new(127, 0, 0, 1)
+}
+
+fn default_metrics_port() -> u16 {
+    9090
+}
+
+fn default_admin_port() -> u16 {
+    8089
+}
+
+fn default_shutdown_timeout() -> u64 {
+    10
+}
+
+fn default_hot_reload() -> bool {
+    true
+}
+
+fn default_data_dir() -> PathBuf {
+    PathBuf::from(crate::plugin::PLUGIN_DATA_DIR)
+}
+
+/// WebSocket 原始帧记录设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordConfig {
+    /// 记录文件目录
+    #[serde(default = "default_record_dir")]
+    pub dir: std::path::PathBuf,
+    /// 单个记录文件最大字节数，超出后轮转
+    #[serde(alias = "max-size")]
+    #[serde(default = "default_record_max_size")]
+    pub max_size: u64,
+    /// 保留的历史记录文件数
+    #[serde(alias = "max-files")]
+    #[serde(default = "default_record_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for RecordConfig {
+    fn default() -> Self {
+        RecordConfig {
+            dir: default_record_dir(),
+            max_size: default_record_max_size(),
+            max_files: default_record_max_files(),
+        }
+    }
+}
+
+/// 回放记录文件设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplayConfig {
+    /// 记录文件路径
+    pub path: std::path::PathBuf,
+    /// 回放倍速，为 0 时不等待
+    #[serde(default = "default_replay_speed")]
+    pub speed: f64,
+}
+
+/// Prometheus 指标服务设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetricsConfig {
+    /// Host
+    #[serde(default = "default_local_host")]
+    pub host: std::net::Ipv4Addr,
+    /// Port
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            host: default_local_host(),
+            port: default_metrics_port(),
+        }
+    }
+}
+
+/// 管理 HTTP Api 设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminConfig {
+    /// Host
+    #[serde(default = "default_local_host")]
+    pub host: std::net::Ipv4Addr,
+    /// Port
+    #[serde(default = "default_admin_port")]
+    pub port: u16,
+    /// 请求头 `Authorization: Bearer <token>` 所需 token，支持 `env:`、`file:` 引用
+    #[serde(alias = "access-token")]
+    pub access_token: Secret,
 }
 
 /// nbrs 全局配置
@@ -53,6 +229,18 @@ pub struct GlobalConfig {
     pub nicknames: Vec<String>,
     /// 全局命令起始符设置
     pub command_starts: Vec<String>,
+    /// 关闭时等待 Plugin::shutdown 的秒数
+    #[serde(alias = "shutdown-timeout")]
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
+    /// 监视配置文件并在修改后自动重载
+    #[serde(alias = "hot-reload")]
+    #[serde(default = "default_hot_reload")]
+    pub hot_reload: bool,
+    /// Plugin 数据根目录，如 Matcher 开关的持久化文件
+    #[serde(alias = "data-dir")]
+    #[serde(default = "default_data_dir")]
+    pub data_dir: PathBuf,
 }
 
 /// nbrs bot 配置
@@ -70,9 +258,10 @@ pub struct BotConfig {
     /// 命令起始符设置
     #[serde(default)]
     pub command_starts: Vec<String>,
+    /// Onebot authorization，支持 `env:`、`file:` 引用
     #[serde(alias = "access-token")]
     #[serde(default)]
-    access_token: String, // Onebot authorization
+    access_token: Secret,
     /// 正向 WS 地址
     #[serde(default)]
     pub ws_server: String,
@@ -87,39 +276,216 @@ impl Default for NoneBotConfig {
                 superusers: vec![],
                 nicknames: vec![],
                 command_starts: vec!["/".to_owned()],
+                shutdown_timeout: default_shutdown_timeout(),
+                hot_reload: default_hot_reload(),
+                data_dir: default_data_dir(),
             },
             bots: None,
             ws_server: Some(WebSocketServerConfig {
                 host: std::net::Ipv4Addr::new(127, 0, 0, 1),
                 port: 8088,
-                access_token: String::default(),
+                access_token: Secret::default(),
             }),
+            record: None,
+            replay: None,
+            metrics: None,
+            admin: None,
+            plugins: toml::Table::new(),
+            path: PathBuf::from(CONFIG_PATH),
+            plugin_secrets: BTreeSet::new(),
         }
     }
 }
 
 impl NoneBotConfig {
-    /// 从配置文件读取配置
-    pub fn load() -> Self {
-        use colored::*;
-        let config: NoneBotConfig;
-        let config_pathbuf = std::path::PathBuf::from(&CONFIG_PATH);
-        if !config_pathbuf.exists() {
-            config = NoneBotConfig::default();
-            let config_string = toml::to_string(&config).unwrap();
-            std::fs::write(&config_pathbuf, config_string).unwrap();
-            tracing::info!("{}", "未发现配置文件，已新建配置文件。".green());
+    /// 配置文件路径，优先使用环境变量 `NBRS_CONFIG`
+    pub fn default_path() -> PathBuf {
+        std::env::var_os(CONFIG_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(CONFIG_PATH))
+    }
+
+    /// 从 `default_path` 读取配置
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(Self::default_path())
+    }
+
+    /// 从指定路径读取配置并校验
+    ///
+    /// 配置文件不存在时使用默认配置，`NBRS_` 开头的环境变量覆盖文件中的同名项，
+    /// 层级以 `__` 分隔，如 `NBRS_GLOBAL__SUPERUSERS=123,456`、`NBRS_WS_SERVER__PORT=8080`。
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::load_with_env(path.as_ref(), None)
+    }
+
+    fn load_with_env(
+        path: &Path,
+        env: Option<HashMap<String, String>>,
+    ) -> Result<Self, ConfigError> {
+        let file = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+            config::File::from_str(&content, config::FileFormat::Toml)
         } else {
-            config = Config::builder()
-                .add_source(config::File::with_name(CONFIG_PATH))
-                .build()
-                .unwrap()
-                .try_deserialize::<NoneBotConfig>()
-                .unwrap();
+            event!(
+                Level::WARN,
+                path = %path.display(),
+                "未发现配置文件，使用默认配置。"
+            );
+            let content = toml::to_string(&NoneBotConfig::default()).unwrap();
+            config::File::from_str(&content, config::FileFormat::Toml)
+        };
+        let mut environment = config::Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator("_")
+            .separator("__")
+            .list_separator(",")
+            .try_parsing(true)
+            .source(env);
+        for key in ENV_LIST_KEYS {
+            environment = environment.with_list_parse_key(key);
+        }
+        let table: toml::Table = Config::builder()
+            .add_source(file)
+            .add_source(environment)
+            .build()
+            .and_then(Config::try_deserialize)
+            .map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+        // 经 serde_path_to_error 反序列化以便得到出错的配置项路径
+        let mut config: NoneBotConfig = serde_path_to_error::deserialize(toml::Value::Table(table))
+            .map_err(|e| {
+                let key = match e.path().to_string() {
+                    key if key == "." => "<root>".to_string(),
+                    key => key,
+                };
+                ConfigError::Invalid(vec![(key, e.into_inner().message().to_string())])
+            })?;
+        config.path = path.to_path_buf();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 校验配置，返回所有不合法的配置项
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = vec![];
+        let mut invalid = |key: String, message: &str| errors.push((key, message.to_string()));
+        if self.global.command_starts.is_empty() {
+            invalid("global.command_starts".to_string(), "must not be empty");
+        }
+        if let Some(ws_server) = &self.ws_server {
+            if ws_server.port == 0 {
+                invalid("ws_server.port".to_string(), "must not be 0");
+            }
         }
+        for (bot_id, bot) in self.bots.iter().flatten() {
+            if !bot.ws_server.is_empty()
+                && !bot.ws_server.starts_with("ws://")
+                && !bot.ws_server.starts_with("wss://")
+            {
+                invalid(
+                    format!("bots.{}.ws_server", bot_id),
+                    "must start with ws:// or wss://",
+                );
+            }
+        }
+        if let Some(record) = &self.record {
+            if record.max_size == 0 {
+                invalid("record.max_size".to_string(), "must not be 0");
+            }
+        }
+        if let Some(replay) = &self.replay {
+            if replay.speed.is_nan() || replay.speed < 0.0 {
+                invalid("replay.speed".to_string(), "must not be negative");
+            }
+        }
+        if let Some(metrics) = &self.metrics {
+            if metrics.port == 0 {
+                invalid("metrics.port".to_string(), "must not be 0");
+            }
+        }
+        if let Some(admin) = &self.admin {
+            if admin.port == 0 {
+                invalid("admin.port".to_string(), "must not be 0");
+            }
+            if admin.access_token.is_empty() {
+                invalid("admin.access_token".to_string(), "must not be empty");
+            }
+        }
+        for (name, value) in &self.plugins {
+            if !value.is_table() {
+                invalid(format!("plugins.{}", name), "must be a table");
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    /// 配置文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `[plugins.<name>]` 原始配置，名称不区分大小写
+    pub fn plugin_section(&self, name: &str) -> Option<&toml::Value> {
+        self.plugins
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// 读取 `[plugins.<name>]` 配置，名称不区分大小写
+    ///
+    /// 缺少该配置或解析失败时使用默认值，不会写回配置文件，解析失败时输出错误。
+    pub fn plugin_config<C>(&mut self, name: &str) -> C
+    where
+        C: DeserializeOwned + Serialize + Default,
+    {
+        let config = match self.plugin_section(name) {
+            Some(value) => match value.clone().try_into() {
+                Ok(config) => config,
+                Err(e) => {
+                    event!(
+                        Level::ERROR,
+                        plugin = name,
+                        "Parse config [plugins.{}] fail: {}",
+                        name,
+                        e
+                    );
+                    C::default()
+                }
+            },
+            None => C::default(),
+        };
+        self.record_plugin_secrets(name, &config);
         config
     }
 
+    /// 记录 Plugin 配置中类型为 `Secret` 的配置项
+    fn record_plugin_secrets<C: Serialize>(&mut self, name: &str, config: &C) {
+        let Ok(value) = crate::secret::mark(|| toml::Value::try_from(config)) else {
+            return;
+        };
+        let prefix = format!("plugins.{}", name.to_ascii_lowercase());
+        for (key, (_, secret)) in crate::reload::flatten_value(&prefix, value) {
+            if secret {
+                self.plugin_secrets.insert(key.to_ascii_lowercase());
+            }
+        }
+    }
+
+    /// 配置项是否为 Plugin 配置中的 `Secret`
+    pub(crate) fn is_plugin_secret(&self, key: &str) -> bool {
+        self.plugin_secrets.contains(&key.to_ascii_lowercase())
+    }
+
+    /// 沿用已记录的 Plugin `Secret` 配置项，用于重新读取的配置
+    pub(crate) fn inherit_plugin_secrets(&mut self, old: &NoneBotConfig) {
+        self.plugin_secrets
+            .extend(old.plugin_secrets.iter().cloned());
+    }
+
     /// 生成 BotConfig
     pub fn gen_bot_config(&self, bot_id: &str) -> BotConfig {
         let mut rbotconfig = BotConfig {
@@ -127,7 +493,7 @@ impl NoneBotConfig {
             superusers: self.global.superusers.clone(),
             nicknames: self.global.nicknames.clone(),
             command_starts: self.global.command_starts.clone(),
-            access_token: String::default(),
+            access_token: Secret::default(),
             ws_server: String::default(),
         };
 
@@ -159,15 +525,14 @@ impl NoneBotConfig {
             global: if let Some(ws_server_config) = &self.ws_server {
                 ws_server_config.access_token.clone()
             } else {
-                String::default()
+                Secret::default()
             },
             bots: HashMap::default(),
         };
         if let Some(bots) = &self.bots {
             for (bot_id, bot) in bots {
                 if !bot.access_token.is_empty() {
-                    at.bots
-                        .insert(bot_id.to_string(), bot.access_token.to_string());
+                    at.bots.insert(bot_id.to_string(), bot.access_token.clone());
                 }
             }
         }
@@ -175,36 +540,31 @@ impl NoneBotConfig {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct AccessToken {
-    pub global: String,
-    pub bots: HashMap<String, String>,
+    pub global: Secret,
+    pub bots: HashMap<String, Secret>,
 }
 
 impl AccessToken {
     pub fn get(&self, bot_id: &str) -> String {
-        if let Some(a) = self.bots.get(bot_id) {
-            format!("Bearer {}", a)
-        } else {
-            format!("Bearer {}", &self.global)
-        }
+        format!("Bearer {}", self.secret(bot_id).expose())
     }
 
-    pub fn check_auth(&self, bot_id: &str, token: Option<String>) -> bool {
-        let access_token = if let Some(a) = self.bots.get(bot_id) {
-            a
-        } else {
-            &self.global
-        };
+    fn secret(&self, bot_id: &str) -> &Secret {
+        self.bots.get(bot_id).unwrap_or(&self.global)
+    }
 
+    pub fn check_auth(&self, bot_id: &str, token: Option<String>) -> bool {
+        let access_token = self.secret(bot_id);
         if access_token.is_empty() {
             return true;
         }
 
-        fn check(head: &str, token: &str, access_token: &str) -> bool {
+        fn check(head: &str, token: &str, access_token: &Secret) -> bool {
             if token.starts_with(head) {
                 let token = crate::utils::remove_space(&token.replace(head, ""));
-                if token == access_token {
+                if access_token.matches(&token) {
                     return true;
                 }
             }
@@ -219,12 +579,77 @@ impl AccessToken {
         if !result {
             event!(
                 Level::WARN,
-                "Access Token match fail Bot:[{}] Token:{:?}",
-                bot_id.red(),
-                token
+                bot_id,
+                "Access Token match fail: {}",
+                if token.is_some() {
+                    "wrong token"
+                } else {
+                    "missing Authorization header"
+                }
             );
         }
 
         result
     }
 }
+
+#[test]
+fn config_load_test() {
+    let path = std::env::temp_dir().join(format!("nbrs-config-{}.toml", uuid::Uuid::new_v4()));
+    let load = |content: &str, env: &[(&str, &str)]| {
+        std::fs::write(&path, content).unwrap();
+        let env = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        NoneBotConfig::load_with_env(&path, Some(env))
+    };
+    let base = toml::to_string(&NoneBotConfig::default()).unwrap()
+        + "[plugins.Moli]\ntrigger = \"小雨\"\n";
+
+    let config = load(
+        &base,
+        &[
+            ("NBRS_GLOBAL__SUPERUSERS", "123,456"),
+            ("NBRS_WS_SERVER__PORT", "8080"),
+            ("NBRS_WS_SERVER__ACCESS_TOKEN", "123456"),
+            ("NBRS_CONFIG", "ignored.toml"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(config.global.superusers, vec!["123", "456"]);
+    let ws_server = config.ws_server.as_ref().unwrap();
+    assert_eq!(ws_server.port, 8080);
+    assert_eq!(ws_server.access_token.expose(), "123456");
+    assert!(!format!("{:?}", ws_server).contains("123456"));
+    assert_eq!(config.path(), path);
+    let moli: toml::Table = config.clone().plugin_config("Moli");
+    assert_eq!(moli["trigger"].as_str(), Some("小雨"));
+
+    // 缺少配置时使用默认值，不写回配置文件
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct SaverConfig {
+        keyword: String,
+    }
+    let saver: SaverConfig = config.clone().plugin_config("MsgSaver");
+    assert_eq!(saver, SaverConfig::default());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), base);
+
+    let err = load(&base.replace("127.0.0.1", "localhost:80"), &[]).unwrap_err();
+    assert!(err.to_string().contains("ws_server.host"), "{}", err);
+
+    let err = load(&(base.clone() + "[metrics]\nport = \"x\"\n"), &[]).unwrap_err();
+    assert!(
+        matches!(&err, ConfigError::Invalid(e) if e[0].0 == "metrics.port"),
+        "{}",
+        err
+    );
+
+    let err = load(&base.replace("[\"/\"]", "[]"), &[]).unwrap_err();
+    assert!(
+        matches!(&err, ConfigError::Invalid(e) if e[0].0 == "global.command_starts"),
+        "{}",
+        err
+    );
+    std::fs::remove_file(path).ok();
+}
@@ -1,7 +1,12 @@
+use tracing::{event, Level};
 use uuid::Uuid;
 
 use crate::Nonebot;
 
+/// WebSocket 原始帧记录
+pub mod record;
+/// 回放记录文件
+pub mod replay;
 pub mod revs_ws;
 pub mod utils;
 pub mod ws;
@@ -10,6 +15,17 @@ pub async fn load_connection_task(nonebot: &Nonebot) {
     let access_token = nonebot.config.gen_access_token();
     let mut tasks = nonebot.tasks.lock().await;
 
+    let recorder = match &nonebot.config.record {
+        Some(record_config) => match record::Recorder::new(record_config.clone()) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                event!(Level::ERROR, "Start WebSocket recorder fail: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
     if let Some(ws_server_config) = &nonebot.config.ws_server {
         tasks.insert(
             Uuid::new_v4(),
@@ -19,6 +35,8 @@ pub async fn load_connection_task(nonebot: &Nonebot) {
                 nonebot.event_sender.clone(),
                 nonebot.action_sender.clone(),
                 access_token.clone(),
+                recorder.clone(),
+                nonebot.shutdown_sender.subscribe(),
             ))),
         );
     }
@@ -34,9 +52,22 @@ pub async fn load_connection_task(nonebot: &Nonebot) {
                         nonebot.event_sender.clone(),
                         nonebot.action_sender.clone(),
                         access_token.clone(),
+                        recorder.clone(),
+                        nonebot.shutdown_sender.subscribe(),
                     ))),
                 );
             }
         }
     }
+
+    if let Some(replay_config) = &nonebot.config.replay {
+        tasks.insert(
+            Uuid::new_v4(),
+            Box::pin(tokio::spawn(replay::run(
+                replay_config.clone(),
+                nonebot.event_sender.clone(),
+                nonebot.action_sender.clone(),
+            ))),
+        );
+    }
 }
@@ -0,0 +1,215 @@
+use crate::config::RecordConfig;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use tracing::{event, Level};
+
+/// 记录文件名
+pub static RECORD_FILE: &str = "traffic.jsonl";
+
+/// 帧方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Onebot -> nbrs
+    In,
+    /// nbrs -> Onebot
+    Out,
+}
+
+/// 一条 WebSocket 原始帧记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub bot_id: String,
+    pub direction: Direction,
+    /// 原始文本
+    pub data: String,
+}
+
+/// WebSocket 原始帧记录器
+///
+/// 以 JSONL 格式追加写入 `dir/traffic.jsonl`，超出 `max_size` 后轮转为
+/// `traffic.jsonl.1` ... `traffic.jsonl.{max_files}`。Clone 后写入同一文件。
+///
+/// 文件写入在独立线程中进行，`record` 不阻塞连接所在的异步任务；
+/// 所有 Clone 被 Drop 后写入线程退出。
+#[derive(Clone)]
+pub struct Recorder {
+    config: RecordConfig,
+    sender: mpsc::Sender<Command>,
+}
+
+/// 写入线程指令
+enum Command {
+    Write(String),
+    /// 写完此前的帧后回复
+    Flush(mpsc::SyncSender<()>),
+}
+
+struct RecorderInner {
+    config: RecordConfig,
+    file: Option<File>,
+    size: u64,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// 新建记录器并启动写入线程，目录不存在时自动创建
+    pub fn new(config: RecordConfig) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        let file = open_append(&config.dir.join(RECORD_FILE))?;
+        let size = file.metadata()?.len();
+        let mut inner = RecorderInner {
+            config: config.clone(),
+            file: Some(file),
+            size,
+        };
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("nbrs-recorder".to_string())
+            .spawn(move || inner.run(receiver))?;
+        event!(
+            Level::INFO,
+            "Recording WebSocket frames to {}",
+            config.dir.display()
+        );
+        Ok(Recorder { config, sender })
+    }
+
+    /// 记录一帧，写入失败仅输出警告
+    pub fn record(&self, bot_id: &str, direction: Direction, data: &str) {
+        let frame = Frame {
+            time: chrono::Local::now().timestamp_millis(),
+            bot_id: bot_id.to_string(),
+            direction,
+            data: data.to_string(),
+        };
+        let mut line = serde_json::to_string(&frame).unwrap();
+        line.push('\n');
+        if self.sender.send(Command::Write(line)).is_err() {
+            event!(Level::WARN, "Recorder writer thread exited");
+        }
+    }
+
+    /// 阻塞等待此前记录的帧写入完成
+    pub fn flush(&self) {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        if self.sender.send(Command::Flush(sender)).is_ok() {
+            receiver.recv().ok();
+        }
+    }
+}
+
+impl RecorderInner {
+    fn run(&mut self, receiver: mpsc::Receiver<Command>) {
+        for command in receiver {
+            match command {
+                Command::Write(line) => {
+                    if let Err(e) = self.write(line.as_bytes()) {
+                        event!(Level::WARN, "Record WebSocket frame fail: {}", e);
+                    }
+                }
+                Command::Flush(done) => {
+                    done.send(()).ok();
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, line: &[u8]) -> std::io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.config.max_size {
+            self.rotate()?;
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(open_append(&self.path(0))?),
+        };
+        file.write_all(line)?;
+        file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        self.size = 0;
+        let max_files = self.config.max_files;
+        if max_files == 0 {
+            return std::fs::remove_file(self.path(0));
+        }
+        let oldest = self.path(max_files);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for i in (0..max_files).rev() {
+            let path = self.path(i);
+            if path.exists() {
+                std::fs::rename(path, self.path(i + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.config.dir.join(RECORD_FILE),
+            i => self.config.dir.join(format!("{}.{}", RECORD_FILE, i)),
+        }
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// 读取记录文件，跳过无法解析的行
+pub fn read_frames(path: &Path) -> std::io::Result<Vec<Frame>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => event!(Level::WARN, "Skip broken record line: {}", e),
+        }
+    }
+    Ok(frames)
+}
+
+#[test]
+fn recorder_test() {
+    let dir = std::env::temp_dir().join(format!("nbrs-record-{}", uuid::Uuid::new_v4()));
+    let recorder = Recorder::new(RecordConfig {
+        dir: dir.clone(),
+        max_size: 200,
+        max_files: 1,
+    })
+    .unwrap();
+    for i in 0..6 {
+        recorder.record("10000", Direction::In, &format!("{{\"frame\":{}}}", i));
+    }
+    recorder.record("10000", Direction::Out, "{\"action\":\"send_msg\"}");
+    recorder.flush();
+
+    let current = read_frames(&dir.join(RECORD_FILE)).unwrap();
+    let rotated = read_frames(&dir.join(format!("{}.1", RECORD_FILE))).unwrap();
+    assert!(!dir.join(format!("{}.2", RECORD_FILE)).exists());
+    assert_eq!(current.last().unwrap().direction, Direction::Out);
+    assert!(!rotated.is_empty());
+    assert!(current.len() + rotated.len() < 7);
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,93 @@
+use super::record::{read_frames, Direction};
+use super::utils::dispatch_recv;
+use crate::config::ReplayConfig;
+use crate::{ActionSender, ApiChannelItem, EventSender};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{event, Level};
+
+/// 回放记录文件
+///
+/// 按记录中的时间间隔（除以 `speed`）将 Onebot 发来的帧重新发送至 EventSender，
+/// 记录中出现的 Bot 将被添加至 Nonebot，其 Api 调用仅输出日志不会被发送。
+pub async fn run(config: ReplayConfig, event_sender: EventSender, action_sender: ActionSender) {
+    let frames = match read_frames(&config.path) {
+        Ok(frames) => frames,
+        Err(e) => {
+            event!(
+                Level::ERROR,
+                "Read replay file {} fail: {}",
+                config.path.display(),
+                e
+            );
+            return;
+        }
+    };
+    event!(
+        Level::INFO,
+        "Replaying {} frames from {} at {}x",
+        frames.len(),
+        config.path.display(),
+        config.speed
+    );
+
+    let mut bots = HashMap::new();
+    let mut last_time: Option<i64> = None;
+    for frame in frames.iter().filter(|f| f.direction == Direction::In) {
+        if let Some(last_time) = last_time {
+            if config.speed > 0.0 {
+                let delay = (frame.time - last_time).max(0) as f64 / config.speed;
+                tokio::time::sleep(Duration::from_millis(delay as u64)).await;
+            }
+        }
+        last_time = Some(frame.time);
+
+        if !bots.contains_key(&frame.bot_id) {
+            let apiresp_watch_sender = add_replay_bot(&frame.bot_id, &action_sender).await;
+            bots.insert(frame.bot_id.clone(), apiresp_watch_sender);
+        }
+        dispatch_recv(&frame.data, &event_sender, &bots[&frame.bot_id]).await;
+    }
+    event!(Level::INFO, "Replay finished");
+}
+
+/// 添加回放 Bot，返回其 ApiResp watch sender
+async fn add_replay_bot(
+    bot_id: &str,
+    action_sender: &ActionSender,
+) -> watch::Sender<crate::api_resp::ApiResp> {
+    let (api_sender, mut api_receiver) = mpsc::channel(32);
+    let (apiresp_watch_sender, api_resp_watcher) = watch::channel(crate::api_resp::ApiResp {
+        status: "init".to_string(),
+        retcode: 0,
+        data: crate::api_resp::RespData::None,
+        echo: "".to_string(),
+    });
+    action_sender
+        .send(crate::Action::AddBot {
+            bot_id: bot_id.to_string(),
+            api_sender,
+            action_sender: action_sender.clone(),
+            api_resp_watcher,
+        })
+        .await
+        .unwrap();
+
+    let bot_id = bot_id.to_string();
+    tokio::spawn(async move {
+        while let Some(item) = api_receiver.recv().await {
+            if let ApiChannelItem::Api(api) = item {
+                event!(
+                    Level::INFO,
+                    bot_id,
+                    action = %api.action(),
+                    echo = %api.get_echo(),
+                    "Replay Bot call Api {:?}",
+                    api
+                );
+            }
+        }
+    });
+    apiresp_watch_sender
+}
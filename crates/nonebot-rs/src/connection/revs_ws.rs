@@ -1,9 +1,11 @@
+use super::record::Recorder;
 use super::utils::handler_web_socket;
+use crate::shutdown::{wait_phase, ShutdownPhase, ShutdownWatcher};
 use crate::{ActionSender, EventSender};
-use colored::*;
 use http::Response as HttpResponse;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
 use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
 use tracing::{event, Level};
 
@@ -14,6 +16,8 @@ pub async fn run(
     event_sender: EventSender,
     action_sender: ActionSender,
     access_token: crate::config::AccessToken,
+    recorder: Option<Recorder>,
+    mut shutdown: ShutdownWatcher,
 ) {
     // bind address to start Tcp server
     let try_socket = TcpListener::bind(std::net::SocketAddrV4::new(host, port)).await;
@@ -21,20 +25,37 @@ pub async fn run(
     event!(Level::INFO, "Serveing at -> ws://{}:{}/ws", host, port);
 
     // lopp wait for connect
+    let mut connections = JoinSet::new();
     loop {
-        match listener.accept().await {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = wait_phase(&mut shutdown, ShutdownPhase::Draining) => break,
+        };
+        match accepted {
             Ok((stream, _)) => {
                 event!(Level::TRACE, "Get a TCP connect");
-                tokio::spawn(accept_connection(
+                connections.spawn(accept_connection(
                     stream,
                     event_sender.clone(),
                     action_sender.clone(),
                     access_token.clone(),
+                    recorder.clone(),
+                    shutdown.clone(),
                 ));
             }
             Err(e) => event!(Level::WARN, "TCP connect error {}", e),
         }
+        // 回收已结束的连接
+        while connections.try_join_next().is_some() {}
     }
+
+    // 停止接受连接，等待已有连接关闭
+    drop(listener);
+    event!(
+        Level::INFO,
+        "Reverse WebSocket Server stopped accepting connections"
+    );
+    while connections.join_next().await.is_some() {}
 }
 
 /// handle a income tcp connect
@@ -43,6 +64,8 @@ async fn accept_connection(
     event_sender: EventSender,
     action_sender: ActionSender,
     access_token: crate::config::AccessToken,
+    recorder: Option<Recorder>,
+    shutdown: ShutdownWatcher,
 ) {
     // check peer address
     stream
@@ -70,10 +93,10 @@ async fn accept_connection(
                 if client_role == "Universal" && access_token.check_auth(bot_id, auth) {
                     event!(
                         Level::INFO,
-                        "{} Client {} is connectted. The client type is {}",
-                        user_agent.bright_yellow(),
-                        bot_id.red(),
-                        client_role.bright_cyan()
+                        bot_id,
+                        user_agent,
+                        client_role,
+                        "Client is connectted"
                     );
                     return Ok(resp);
                 }
@@ -114,6 +137,8 @@ async fn accept_connection(
         apiresp_watch_sender,
         receiver,
         output_bot_id,
+        recorder,
+        shutdown,
     )
     .await;
 }
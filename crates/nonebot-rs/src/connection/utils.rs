@@ -1,6 +1,7 @@
+use super::record::{Direction, Recorder};
+use crate::shutdown::{wait_phase, ShutdownPhase, ShutdownWatcher};
 use crate::{event::Event, ActionSender, EventSender};
 use async_recursion::async_recursion;
-use colored::*;
 use futures_util::{stream::SplitStream, SinkExt, StreamExt};
 use tokio::{
     net::TcpStream,
@@ -9,6 +10,7 @@ use tokio::{
 use tokio_tungstenite::{tungstenite::Message as TuMessage, WebSocketStream};
 use tracing::{event, Level};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn handler_web_socket(
     socket: WebSocketStream<TcpStream>,
     event_sender: EventSender,
@@ -16,11 +18,15 @@ pub async fn handler_web_socket(
     apiresp_watch_sender: tokio::sync::watch::Sender<crate::ApiResp>,
     mut api_receiver: tokio::sync::mpsc::Receiver<crate::ApiChannelItem>,
     bot_id: String,
+    recorder: Option<Recorder>,
+    mut shutdown: ShutdownWatcher,
 ) {
     // 将 websocket 接收流与发送流分离
     let (mut sink, mut stream) = socket.split();
     // 接收消息
     let another_event_sender = event_sender.clone();
+    let income_recorder = recorder.clone();
+    let outcome_bot_id = bot_id.clone();
     let income = async move {
         loop {
             let r = stream_recv(
@@ -29,6 +35,7 @@ pub async fn handler_web_socket(
                 &action_sender,
                 &apiresp_watch_sender,
                 bot_id.clone(),
+                income_recorder.as_ref(),
             )
             .await;
             if let Some(s) = r {
@@ -40,28 +47,34 @@ pub async fn handler_web_socket(
     };
     // 发送消息
     let outcome = async move {
-        while let Some(data) = api_receiver.recv().await {
+        loop {
+            let data = tokio::select! {
+                data = api_receiver.recv() => data,
+                _ = wait_phase(&mut shutdown, ShutdownPhase::Closing) => {
+                    event!(Level::INFO, bot_id = %outcome_bot_id, "Closing WebSocket");
+                    sink.close().await.ok();
+                    return;
+                }
+            };
+            let Some(data) = data else {
+                return;
+            };
             match data {
                 // Onebot Api
                 crate::ApiChannelItem::Api(api) => {
                     let json_string = serde_json::to_string(&api).unwrap();
+                    if let Some(recorder) = &recorder {
+                        recorder.record(&outcome_bot_id, Direction::Out, &json_string);
+                    }
                     sink.send(TuMessage::text(json_string)).await.unwrap();
                 }
                 // temp Matcher event
                 crate::ApiChannelItem::MessageEvent(_) => {
-                    event!(
-                        Level::WARN,
-                        "{}",
-                        "WedSocket接受端接收到错误Event消息".bright_red()
-                    );
+                    event!(Level::WARN, "WedSocket接受端接收到错误Event消息");
                 }
                 // temp Matcher Timeout
                 crate::ApiChannelItem::TimeOut => {
-                    event!(
-                        Level::WARN,
-                        "{}",
-                        "WedSocket接受端接收到错误TimeOut消息".bright_red()
-                    );
+                    event!(Level::WARN, "WedSocket接受端接收到错误TimeOut消息");
                 } // 忽视 event 该 receiver 永不应该收到 event
             }
         }
@@ -76,30 +89,21 @@ async fn stream_recv(
     action_sender: &ActionSender,
     apiresp_watch_sender: &watch::Sender<crate::api_resp::ApiResp>,
     bot_id: String,
+    recorder: Option<&Recorder>,
 ) -> Option<SplitStream<WebSocketStream<TcpStream>>> {
     let (msg, next_stream) = stream.into_future().await;
-    if let Some(msg) = msg {
-        use crate::event::RecvItem;
-        if let Ok(msg) = msg {
-            let data: serde_json::Result<RecvItem> = serde_json::from_str(msg.to_text().unwrap());
-            match data {
-                Ok(data) => match data {
-                    RecvItem::Event(event) => send_event(event_sender, event).await,
-                    RecvItem::ApiResp(api_resp) => {
-                        apiresp_watch_sender.send(api_resp).unwrap();
-                    }
-                },
-                Err(e) => {
-                    event!(
-                        Level::ERROR,
-                        "Serialize msg failed! Msg:{:?}\nError:{}",
-                        msg.to_text().unwrap(),
-                        e
-                    );
-                }
+    match msg {
+        // Ping/Pong 由 tungstenite 自动处理
+        Some(Ok(msg)) if msg.is_ping() || msg.is_pong() => {}
+        Some(Ok(msg)) if !msg.is_close() => {
+            let text = msg.to_text().unwrap();
+            if let Some(recorder) = recorder {
+                recorder.record(&bot_id, Direction::In, text);
             }
-        } else {
-            event!(Level::WARN, "Bot [{}] disconnect", bot_id.to_string().red());
+            dispatch_recv(text, event_sender, apiresp_watch_sender).await;
+        }
+        _ => {
+            event!(Level::WARN, bot_id = %bot_id, "Bot disconnect");
             action_sender
                 .send(crate::Action::RemoveBot { bot_id })
                 .await
@@ -110,6 +114,36 @@ async fn stream_recv(
     Some(next_stream)
 }
 
+/// 解析 Onebot 发来的文本帧并分发 Event 或 ApiResp
+pub async fn dispatch_recv(
+    text: &str,
+    event_sender: &EventSender,
+    apiresp_watch_sender: &watch::Sender<crate::api_resp::ApiResp>,
+) {
+    use crate::event::RecvItem;
+    let data: serde_json::Result<RecvItem> = serde_json::from_str(text);
+    match data {
+        Ok(data) => match data {
+            RecvItem::Event(event) => {
+                crate::metrics::metrics().event_received(&event);
+                send_event(event_sender, event).await
+            }
+            RecvItem::ApiResp(api_resp) => {
+                apiresp_watch_sender.send(api_resp).unwrap();
+            }
+        },
+        Err(e) => {
+            crate::metrics::metrics().recv_failed();
+            event!(
+                Level::ERROR,
+                "Serialize msg failed! Msg:{:?}\nError:{}",
+                text,
+                e
+            );
+        }
+    }
+}
+
 #[async_recursion]
 pub async fn send_event(sender: &broadcast::Sender<Event>, e: Event) -> () {
     match sender.send(e) {
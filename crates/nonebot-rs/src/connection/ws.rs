@@ -1,7 +1,8 @@
+use super::record::{Direction, Recorder};
 use super::utils::handler_web_socket;
+use crate::shutdown::{wait_phase, ShutdownPhase, ShutdownWatcher};
 use crate::{builtin::matcher::prelude::SelfId, event::Event, ActionSender, EventSender};
 use async_recursion::async_recursion;
-use colored::*;
 use futures_util::StreamExt;
 use http::{header::USER_AGENT, Uri};
 use tokio::{
@@ -22,6 +23,8 @@ pub async fn run(
     event_sender: EventSender,
     action_sender: ActionSender,
     access_token: crate::config::AccessToken,
+    recorder: Option<Recorder>,
+    mut shutdown: ShutdownWatcher,
 ) {
     single_socket(
         &url,
@@ -29,10 +32,25 @@ pub async fn run(
         event_sender.clone(),
         action_sender.clone(),
         access_token.clone(),
+        recorder.clone(),
+        shutdown.clone(),
+    )
+    .await;
+    // 关闭时不再重连
+    tokio::select! {
+        _ = tokio::time::sleep(std::time::Duration::from_secs(5)) => {}
+        _ = wait_phase(&mut shutdown, ShutdownPhase::Draining) => return,
+    }
+    run(
+        url,
+        bot_id,
+        event_sender,
+        action_sender,
+        access_token,
+        recorder,
+        shutdown,
     )
     .await;
-    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-    run(url, bot_id, event_sender, action_sender, access_token).await;
 }
 
 pub async fn single_socket(
@@ -41,6 +59,8 @@ pub async fn single_socket(
     event_sender: EventSender,
     action_sender: ActionSender,
     access_token: crate::config::AccessToken,
+    recorder: Option<Recorder>,
+    shutdown: ShutdownWatcher,
 ) {
     let uri: Uri = url.parse().unwrap();
     let addr = format!("{}:{}", uri.host().unwrap(), uri.port().unwrap());
@@ -91,10 +111,13 @@ pub async fn single_socket(
     // println!("{:?}", headers);
     if let Some(Ok(msg)) = stream.next().await {
         let msg = msg.to_text().unwrap();
+        if let Some(recorder) = &recorder {
+            recorder.record(bot_id, Direction::In, msg);
+        }
         let event: Event = serde_json::from_str(msg).unwrap();
         let bot_id = event.get_self_id();
 
-        event!(Level::INFO, "Connectted to Bot {} Server", bot_id.red());
+        event!(Level::INFO, bot_id = %bot_id, "Connectted to Bot Server");
 
         // add bot to Nonebot
         action_sender
@@ -115,6 +138,8 @@ pub async fn single_socket(
             apiresp_watch_sender,
             receiver,
             bot_id,
+            recorder,
+            shutdown,
         )
         .await;
     }
@@ -38,8 +38,25 @@ pub enum Event {
 /// Nonebot Event
 #[derive(Debug, Clone)]
 pub enum NoneBotEvent {
-    BotConnect { bot: crate::Bot },
-    BotDisconnect { bot: crate::Bot },
+    BotConnect {
+        bot: crate::Bot,
+    },
+    BotDisconnect {
+        bot: crate::Bot,
+    },
+    /// Nonebot 即将关闭，随后调用 `Plugin::shutdown`
+    Shutdown,
+    /// 设置 Matcher 开关，由 `Action::DisableMatcher` 发出
+    DisableMatcher {
+        name: String,
+        group_id: Option<String>,
+        disable: bool,
+    },
+    /// 重载后 `[plugins.<name>]` 配置发生变化，name 为小写，config 为 None 时该配置已被删除
+    PluginConfigChanged {
+        name: String,
+        config: Option<toml::Value>,
+    },
 }
 
 /// 消息事件
@@ -384,7 +401,43 @@ impl SelfId for Event {
             Event::Nonebot(e) => match e {
                 NoneBotEvent::BotConnect { bot } => bot.bot_id.clone(),
                 NoneBotEvent::BotDisconnect { bot } => bot.bot_id.clone(),
+                NoneBotEvent::Shutdown
+                | NoneBotEvent::DisableMatcher { .. }
+                | NoneBotEvent::PluginConfigChanged { .. } => String::default(),
             },
         }
     }
 }
+
+/// `get_group_id()` trait
+pub trait GroupId {
+    /// 事件来源群号，非群事件返回 None
+    fn get_group_id(&self) -> Option<String>;
+}
+
+impl GroupId for MessageEvent {
+    fn get_group_id(&self) -> Option<String> {
+        match self {
+            MessageEvent::Private(_) => None,
+            MessageEvent::Group(g) => Some(g.group_id.clone()),
+        }
+    }
+}
+
+impl GroupId for NoticeEvent {
+    fn get_group_id(&self) -> Option<String> {
+        self.group_id.clone()
+    }
+}
+
+impl GroupId for RequestEvent {
+    fn get_group_id(&self) -> Option<String> {
+        self.group_id.clone()
+    }
+}
+
+impl GroupId for MetaEvent {
+    fn get_group_id(&self) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,121 @@
+use crate::shutdown::{wait_phase, ShutdownPhase, ShutdownWatcher};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::Ipv4Addr;
+use tokio::net::TcpListener;
+use tracing::{event, Level};
+
+/// nbrs 内建 HTTP 服务响应
+pub(crate) type HttpResponse = Response<Full<Bytes>>;
+
+/// 绑定 HTTP 服务监听地址，失败时输出错误并返回 None
+pub(crate) async fn bind(name: &'static str, host: Ipv4Addr, port: u16) -> Option<TcpListener> {
+    match TcpListener::bind((host, port)).await {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            event!(Level::ERROR, server = name, "Http server bind fail: {}", e);
+            None
+        }
+    }
+}
+
+/// 在已绑定的 listener 上启动 HTTP 服务，每个请求交由 `handle` 处理，
+/// 进入 `Closing` 阶段后返回
+pub(crate) async fn serve<H, F>(
+    name: &'static str,
+    listener: TcpListener,
+    mut shutdown: ShutdownWatcher,
+    handle: H,
+) where
+    H: Fn(Request<Incoming>) -> F + Clone + Send + 'static,
+    F: Future<Output = HttpResponse> + Send + 'static,
+{
+    if let Ok(addr) = listener.local_addr() {
+        event!(
+            Level::INFO,
+            server = name,
+            "Serving {} at -> http://{}",
+            name,
+            addr
+        );
+    }
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = wait_phase(&mut shutdown, ShutdownPhase::Closing) => return,
+        };
+        match accepted {
+            Ok((stream, _)) => {
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| {
+                        let response = handle(req);
+                        async move { Ok::<_, Infallible>(response.await) }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        event!(Level::DEBUG, server = name, "Http connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => event!(Level::WARN, server = name, "TCP connect error {}", e),
+        }
+    }
+}
+
+/// 指定 Content-Type 的响应
+pub(crate) fn response(status: StatusCode, content_type: &str, body: Vec<u8>) -> HttpResponse {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, content_type)
+        .body(Full::new(Bytes::from(body)))
+        .unwrap()
+}
+
+/// JSON 响应
+pub(crate) fn json<T: serde::Serialize>(status: StatusCode, body: &T) -> HttpResponse {
+    response(
+        status,
+        "application/json",
+        serde_json::to_vec(body).unwrap_or_default(),
+    )
+}
+
+/// 测试用 HTTP/1.1 请求，返回完整响应文本
+#[cfg(test)]
+pub(crate) async fn test_request(
+    port: u16,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        path,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
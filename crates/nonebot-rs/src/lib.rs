@@ -1,4 +1,8 @@
+extern crate self as nonebot_rs;
+
 mod action;
+/// 管理 HTTP Api
+pub mod admin;
 /// Onebot Api
 pub mod api;
 /// Onebot Api Response
@@ -11,11 +15,29 @@ pub mod config;
 pub mod connection;
 /// Onebot 事件
 pub mod event;
+mod http;
 #[doc(hidden)]
 pub mod message;
+/// Prometheus 指标
+pub mod metrics;
 mod nonebot;
 #[doc(hidden)]
 pub mod plugin;
+/// 配置文件热重载
+pub mod reload;
+/// 配置密钥
+pub mod secret;
+/// 优雅关闭
+pub mod shutdown;
+/// 共享状态
+pub mod state;
+/// Plugin 事件流
+pub mod stream;
+/// Plugin 监视与重启
+pub mod supervisor;
+/// 离线测试工具
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 // /// scheduler Plugin
 // pub mod scheduler;
 mod utils;
@@ -37,7 +59,13 @@ pub use bot::Bot;
 #[doc(inline)]
 pub use message::Message;
 #[doc(inline)]
+pub use nonebot_rs_macros::{command, plugin};
+#[doc(inline)]
 pub use plugin::Plugin;
+#[doc(inline)]
+pub use secret::Secret;
+#[doc(inline)]
+pub use stream::{DropPolicy, EventStream};
 
 // pub use scheduler::Scheduler;
 
@@ -80,9 +108,17 @@ pub struct Nonebot {
     pub bot_sender: BotSender,
     /// Bot Getter
     pub bot_getter: BotGetter,
+    /// Nonebot 共享状态，Plugin 与 Matcher 均可获取
+    state: state::StateMap,
+    /// 广播运行阶段，用于关闭连接
+    shutdown_sender: shutdown::ShutdownSender,
     /// event handler
     plugins: HashMap<uuid::Uuid, Box<dyn Plugin + Send + Sync>>,
-    /// Bot tasks
+    /// Plugin tasks
+    supervisor: supervisor::Supervisor,
+    /// Plugin 健康状态，同时存放于共享状态中
+    health: state::State<supervisor::PluginHealthTable>,
+    /// Connection tasks
     tasks: Mutex<HashMap<uuid::Uuid, Pin<Box<JoinHandle<()>>>>>,
 }
 
@@ -0,0 +1,214 @@
+//! Prometheus 指标
+//!
+//! 指标始终记录于 `prometheus` 默认 Registry，配置 `[metrics]` 后
+//! 在 `http://<host>:<port>/metrics` 输出。Plugin 可使用 `prometheus` 的
+//! `register_*!` 宏注册自定义指标，一并输出。
+
+pub use prometheus;
+
+use crate::config::MetricsConfig;
+use crate::event::{Event, SelfId};
+use crate::http::{response, HttpResponse};
+use crate::shutdown::ShutdownWatcher;
+use hyper::body::Incoming;
+use hyper::{Method, Request, StatusCode};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{event, Level};
+
+/// 临时 Matcher 名称唯一，统一使用此标签
+const TEMP_MATCHER: &str = "<temp>";
+
+/// nbrs 内建指标
+pub(crate) struct Metrics {
+    /// 按类型与 Bot 统计接收的 Event
+    events_received: IntCounterVec,
+    /// 无法解析的 Onebot 消息
+    recv_failures: IntCounter,
+    /// 按 action 与返回状态统计的 Api 调用
+    api_calls: IntCounterVec,
+    /// 等待返回的 Api 调用耗时
+    api_duration: HistogramVec,
+    /// Matcher 匹配次数
+    matcher_hits: IntCounterVec,
+    /// Matcher handler 耗时
+    matcher_duration: HistogramVec,
+    /// 事件流因 broadcast 积压跳过的事件
+    stream_lagged: IntCounterVec,
+    /// 已连接的 Bot
+    connected_bots: IntGauge,
+}
+
+pub(crate) fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| {
+        let registry = prometheus::default_registry();
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels).unwrap();
+            registry.register(Box::new(counter.clone())).unwrap();
+            counter
+        };
+        let histogram = |name: &str, help: &str, labels: &[&str]| {
+            let histogram = HistogramVec::new(HistogramOpts::new(name, help), labels).unwrap();
+            registry.register(Box::new(histogram.clone())).unwrap();
+            histogram
+        };
+        let recv_failures = IntCounter::new(
+            "nonebot_recv_failures_total",
+            "Onebot messages failed to deserialize",
+        )
+        .unwrap();
+        registry.register(Box::new(recv_failures.clone())).unwrap();
+        let connected_bots =
+            IntGauge::new("nonebot_connected_bots", "Currently connected bots").unwrap();
+        registry.register(Box::new(connected_bots.clone())).unwrap();
+        Metrics {
+            events_received: counter(
+                "nonebot_events_received_total",
+                "Events received from Onebot",
+                &["type", "bot_id"],
+            ),
+            recv_failures,
+            api_calls: counter(
+                "nonebot_api_calls_total",
+                "Onebot Api calls",
+                &["action", "status"],
+            ),
+            api_duration: histogram(
+                "nonebot_api_call_duration_seconds",
+                "Onebot Api call latency, only for calls waiting for response",
+                &["action", "status"],
+            ),
+            matcher_hits: counter(
+                "nonebot_matcher_hits_total",
+                "Matched events by matcher",
+                &["matcher"],
+            ),
+            matcher_duration: histogram(
+                "nonebot_matcher_handle_duration_seconds",
+                "Matcher handler duration",
+                &["matcher"],
+            ),
+            stream_lagged: counter(
+                "nonebot_stream_lagged_events_total",
+                "Events skipped by event streams due to broadcast lag",
+                &["stream"],
+            ),
+            connected_bots,
+        }
+    })
+}
+
+impl Metrics {
+    pub(crate) fn event_received(&self, event: &Event) {
+        let event_type = match event {
+            Event::Message(_) => "message",
+            Event::Notice(_) => "notice",
+            Event::Request(_) => "request",
+            Event::Meta(_) => "meta_event",
+            Event::Nonebot(_) => "nonebot",
+        };
+        self.events_received
+            .with_label_values(&[event_type, &event.get_self_id()])
+            .inc();
+    }
+
+    pub(crate) fn recv_failed(&self) {
+        self.recv_failures.inc();
+    }
+
+    /// 记录 Api 调用，`elapsed` 为 None 时为不等待返回的调用
+    pub(crate) fn api_called(&self, action: &str, status: &str, elapsed: Option<Duration>) {
+        self.api_calls.with_label_values(&[action, status]).inc();
+        if let Some(elapsed) = elapsed {
+            self.api_duration
+                .with_label_values(&[action, status])
+                .observe(elapsed.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn matcher_hit(&self, name: &str, temp: bool) {
+        let name = if temp { TEMP_MATCHER } else { name };
+        self.matcher_hits.with_label_values(&[name]).inc();
+    }
+
+    pub(crate) fn matcher_handled(&self, name: &str, temp: bool, elapsed: Duration) {
+        let name = if temp { TEMP_MATCHER } else { name };
+        self.matcher_duration
+            .with_label_values(&[name])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn stream_lagged(&self, name: &str, n: u64) {
+        self.stream_lagged.with_label_values(&[name]).inc_by(n);
+    }
+
+    pub(crate) fn set_connected_bots(&self, n: usize) {
+        self.connected_bots.set(n as i64);
+    }
+}
+
+/// 以 Prometheus 文本格式输出所有已注册指标
+pub fn render() -> String {
+    metrics();
+    let mut buffer = vec![];
+    if let Err(e) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        event!(Level::ERROR, "Encode metrics fail: {}", e);
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// 启动 `/metrics` HTTP 服务，进入 `Closing` 阶段后返回
+pub async fn serve(config: MetricsConfig, shutdown: ShutdownWatcher) {
+    if let Some(listener) = crate::http::bind("metrics", config.host, config.port).await {
+        serve_listener(listener, shutdown).await
+    }
+}
+
+async fn serve_listener(listener: tokio::net::TcpListener, shutdown: ShutdownWatcher) {
+    crate::http::serve("metrics", listener, shutdown, handle).await
+}
+
+async fn handle(req: Request<Incoming>) -> HttpResponse {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => response(
+            StatusCode::OK,
+            TextEncoder::new().format_type(),
+            render().into_bytes(),
+        ),
+        _ => response(StatusCode::NOT_FOUND, "text/plain", vec![]),
+    }
+}
+
+#[tokio::test]
+async fn metrics_test() {
+    metrics().api_called("get_status", "ok", Some(Duration::from_millis(20)));
+    metrics().matcher_hit("metrics_test-1-1", true);
+    metrics().stream_lagged("metrics_test", 3);
+
+    let text = render();
+    assert!(text.contains(
+        "nonebot_api_call_duration_seconds_count{action=\"get_status\",status=\"ok\"} 1"
+    ));
+    assert!(text.contains("nonebot_matcher_hits_total{matcher=\"<temp>\"}"));
+    assert!(text.contains("nonebot_stream_lagged_events_total{stream=\"metrics_test\"} 3"));
+
+    use crate::http::test_request;
+    use crate::shutdown::ShutdownPhase;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = tokio::sync::watch::channel(ShutdownPhase::Running);
+    let server = tokio::spawn(serve_listener(listener, receiver));
+    let response = test_request(port, "GET", "/metrics", &[], "").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("nonebot_api_calls_total"));
+    assert!(test_request(port, "GET", "/", &[], "")
+        .await
+        .starts_with("HTTP/1.1 404"));
+    sender.send_replace(ShutdownPhase::Closing);
+    server.await.unwrap();
+}
@@ -1,5 +1,10 @@
+use crate::event::{Event, NoneBotEvent};
+use crate::shutdown::ShutdownPhase;
+use crate::state::StateMap;
+use crate::supervisor::{Exit, PluginHealth, PluginHealthTable};
 use crate::{ActionSender, ApiChannelItem, ApiResp, Bot, Nonebot, Plugin};
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::{broadcast, mpsc, watch};
 use tracing::{event, Level};
 use uuid::Uuid;
@@ -22,6 +27,7 @@ impl Nonebot {
         );
         self.bots.insert(bot_id.to_string(), bot.clone());
         self.bot_sender.send(self.bots.clone()).unwrap();
+        crate::metrics::metrics().set_connected_bots(self.bots.len());
         bot
     }
 
@@ -30,21 +36,73 @@ impl Nonebot {
         let bot_id = bot_id.to_string();
         let bot = self.bots.remove(&bot_id);
         self.bot_sender.send(self.bots.clone()).unwrap();
+        crate::metrics::metrics().set_connected_bots(self.bots.len());
         bot
     }
 
-    /// 新建一个 Matchers 为空的 Nonebot 结构体
+    /// 读取配置文件新建 Nonebot，读取失败时 panic
     pub fn new() -> Self {
         Default::default()
     }
 
-    /// 添加 Plugin
-    pub fn add_plugin<T>(&mut self, plugin: T) -> &mut Self
+    /// 使用指定配置新建 Nonebot
+    pub fn with_config(nb_config: crate::config::NoneBotConfig) -> Self {
+        let (event_sender, _) = broadcast::channel(1024); // need largo cache when reconnect
+        let (action_sender, action_receiver) = tokio::sync::mpsc::channel(32);
+        let (bot_sender, bot_getter) = watch::channel(HashMap::new());
+        let state = StateMap::new();
+        state.insert(PluginHealthTable::default());
+        state.insert(crate::builtin::matcher::matchers::MatcherTable::default());
+        state.insert(crate::plugin::PluginDataDir(
+            nb_config.global.data_dir.clone(),
+        ));
+        let health = state.get::<PluginHealthTable>().unwrap();
+        Nonebot {
+            bots: Default::default(),
+            config: nb_config,
+            event_sender,
+            action_sender,
+            action_receiver,
+            bot_sender,
+            bot_getter,
+            state,
+            shutdown_sender: watch::channel(ShutdownPhase::Running).0,
+            plugins: Default::default(),
+            supervisor: Default::default(),
+            health,
+            tasks: Default::default(),
+        }
+    }
+
+    /// 添加共享状态，Plugin 与 Matcher 可通过 `State<T>` 获取
+    ///
+    /// 共享状态在 Nonebot 运行结束时释放
+    pub fn manage<T>(&mut self, value: T) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.state.insert(value);
+        self
+    }
+
+    /// Nonebot 共享状态
+    pub fn state(&self) -> &StateMap {
+        &self.state
+    }
+
+    /// Nonebot Action Sender，用于在运行时发送 `Action`
+    pub fn action_sender(&self) -> ActionSender {
+        self.action_sender.clone()
+    }
+
+    /// 添加 Plugin，并传入配置文件中 `[plugins.<name>]` 的配置
+    pub fn add_plugin<T>(&mut self, mut plugin: T) -> &mut Self
     where
         T: Plugin + Send + Sync + 'static,
     {
-        self.plugins
-            .insert(plugin.plugin_info().id, Box::new(plugin));
+        let config = self.config.plugin_config(plugin.plugin_info().name);
+        plugin.set_config(config);
+        self.insert_plugin(Box::new(plugin));
         self
     }
 
@@ -53,65 +111,339 @@ impl Nonebot {
         self.plugins.remove(id);
     }
 
+    pub(crate) fn insert_plugin(&mut self, mut plugin: Box<dyn Plugin + Send + Sync>) {
+        plugin.set_state(self.state.clone());
+        self.plugins.insert(plugin.plugin_info().id, plugin);
+    }
+
     #[doc(hidden)]
-    pub async fn load_plugins_task(&self) {
-        use colored::*;
+    pub fn load_plugins_task(&mut self) {
         event!(Level::INFO, "Loaded Config Successful...");
-        event!(Level::INFO, "{}", "高性能自律実験4号機が稼働中····".red());
-        let mut tasks = self.tasks.lock().await;
-        for plugin in self.plugins.values() {
-            let task = plugin.load(self.event_sender.subscribe(), self.bot_getter.clone());
-            let plugin_info = plugin.plugin_info();
-            tasks.insert(plugin_info.id, Box::pin(task));
-            if plugin.init().is_err() {
+        event!(Level::INFO, "高性能自律実験4号機が稼働中····");
+        let ids: Vec<Uuid> = self.plugins.keys().cloned().collect();
+        for id in ids {
+            self.load_plugin(&id);
+        }
+    }
+
+    /// 所有 Plugin 的健康状态
+    pub fn plugin_health(&self) -> Vec<(Uuid, PluginHealth)> {
+        self.health.all()
+    }
+
+    /// 处理 Plugin 任务退出，按 `Plugin::restart_policy` 重启
+    pub(crate) fn handle_plugin_exit(&mut self, exit: Exit) {
+        match exit {
+            Exit::Finished(id, result) => {
+                let Some(plugin) = self.plugins.get(&id) else {
+                    return;
+                };
+                let plugin_info = plugin.plugin_info();
+                let health = self.supervisor.exited(
+                    &plugin_info,
+                    result,
+                    plugin.restart_policy(),
+                    &self.health,
+                );
+                let reason = health.last_error.as_deref().unwrap_or("task finished");
+                if health.last_error.is_some() {
+                    event!(
+                        Level::ERROR,
+                        plugin = plugin_info.name,
+                        plugin_id = %plugin_info.id,
+                        "Plugin exited ({}): {}",
+                        health.status,
+                        reason
+                    );
+                } else {
+                    event!(
+                        Level::WARN,
+                        plugin = plugin_info.name,
+                        plugin_id = %plugin_info.id,
+                        "Plugin exited ({}): {}",
+                        health.status,
+                        reason
+                    );
+                }
+            }
+            Exit::Restart(id) => {
+                if let Some(plugin) = self.plugins.get(&id) {
+                    let plugin_info = plugin.plugin_info();
+                    event!(
+                        Level::WARN,
+                        plugin = plugin_info.name,
+                        plugin_id = %plugin_info.id,
+                        "Restarting Plugin"
+                    );
+                    self.load_plugin(&id);
+                }
+            }
+        }
+    }
+
+    /// 启动已添加的 Plugin
+    pub(crate) fn load_plugin(&mut self, id: &Uuid) {
+        let Some(plugin) = self.plugins.get(id) else {
+            return;
+        };
+        let task = plugin.load(self.event_sender.subscribe(), self.bot_getter.clone());
+        let plugin_info = plugin.plugin_info();
+        self.supervisor.watch(&plugin_info, task, &self.health);
+        if plugin
+            .init(&crate::plugin::PluginDataDir::get(&self.state))
+            .is_err()
+        {
+            event!(
+                Level::ERROR,
+                plugin = plugin_info.name,
+                plugin_id = %plugin_info.id,
+                "Plugin init error."
+            );
+        }
+        event!(
+            Level::INFO,
+            plugin = plugin_info.name,
+            plugin_id = %plugin_info.id,
+            "Plugin is loaded."
+        );
+    }
+
+    /// 移除运行中的 Plugin
+    ///
+    /// 立即停止监视，关闭函数在独立任务中运行，完成或超时后终止 Plugin 任务，
+    /// 不阻塞 Action 处理
+    pub(crate) fn unload_plugin(&mut self, id: &Uuid) {
+        let Some(plugin) = self.plugins.remove(id) else {
+            event!(Level::WARN, plugin_id = %id, "Removing not exists Plugin");
+            return;
+        };
+        let plugin_info = plugin.plugin_info();
+        let task = self.supervisor.detach(&plugin_info, &self.health);
+        let timeout = Duration::from_secs(self.config.global.shutdown_timeout);
+        tokio::spawn(async move {
+            if tokio::time::timeout(timeout, plugin.shutdown())
+                .await
+                .is_err()
+            {
                 event!(
-                    Level::ERROR,
-                    "Plugin {} {} init error.",
-                    plugin_info.name.red(),
-                    plugin_info.id.to_string().blue()
+                    Level::WARN,
+                    plugin = plugin_info.name,
+                    plugin_id = %plugin_info.id,
+                    "Plugin shutdown timeout."
                 );
             }
+            if let Some(task) = task {
+                task.abort();
+            }
             event!(
                 Level::INFO,
-                "Plugin {} {} is loaded.",
-                plugin_info.name.red(),
-                plugin_info.id.to_string().blue()
+                plugin = plugin_info.name,
+                plugin_id = %plugin_info.id,
+                "Plugin is removed."
             );
-        }
+        });
     }
 
-    async fn task_runner(&self) {
-        let mut tasks = self.tasks.lock().await;
-        for task in tasks.values_mut() {
-            task.await.ok();
+    /// 重新读取配置文件，输出配置变化，经 `Action::ChangeBotConfig` 更新所有 Bot 的 BotConfig，
+    /// 并向配置发生变化的 Plugin 广播 `NoneBotEvent::PluginConfigChanged`
+    pub fn reload_config(&mut self) {
+        let mut config = match crate::config::NoneBotConfig::load_from(self.config.path()) {
+            Ok(config) => config,
+            Err(e) => {
+                event!(Level::ERROR, "Reload config fail: {}", e);
+                return;
+            }
+        };
+        config.inherit_plugin_secrets(&self.config);
+        let changes = crate::reload::diff(&self.config, &config);
+        if changes.is_empty() {
+            event!(Level::DEBUG, "Config unchanged");
+            return;
         }
+        crate::reload::log_changes(&changes);
+        self.config = config;
+
+        let bot_configs: Vec<_> = self
+            .bots
+            .keys()
+            .map(|bot_id| (bot_id.clone(), self.config.gen_bot_config(bot_id)))
+            .collect();
+        let action_sender = self.action_sender.clone();
+        // 在 Action 循环内发送 Action 可能因通道已满而阻塞
+        tokio::spawn(async move {
+            for (bot_id, bot_config) in bot_configs {
+                action_sender
+                    .send(crate::Action::ChangeBotConfig { bot_id, bot_config })
+                    .await
+                    .ok();
+            }
+        });
+        for name in crate::reload::changed_plugins(&changes) {
+            let config = self.config.plugins.get(&name).cloned();
+            self.event_sender
+                .send(Event::Nonebot(NoneBotEvent::PluginConfigChanged {
+                    name,
+                    config,
+                }))
+                .ok();
+        }
+        event!(Level::INFO, "Config reloaded.");
     }
 
-    /// 运行 Nonebot 实例
+    /// 运行 Nonebot 实例，收到 SIGINT、SIGTERM 或 `Action::Shutdown` 后关闭并返回
     pub async fn run(&mut self) {
         crate::connection::load_connection_task(self).await;
-        self.load_plugins_task().await;
-        self.handle_action().await;
-        self.task_runner().await;
+        if self.config.global.hot_reload {
+            self.tasks.lock().await.insert(
+                Uuid::new_v4(),
+                Box::pin(tokio::spawn(crate::reload::watch(
+                    self.config.path().to_path_buf(),
+                    self.action_sender.clone(),
+                    self.shutdown_sender.subscribe(),
+                ))),
+            );
+        }
+        if let Some(metrics) = &self.config.metrics {
+            self.tasks.lock().await.insert(
+                Uuid::new_v4(),
+                Box::pin(tokio::spawn(crate::metrics::serve(
+                    metrics.clone(),
+                    self.shutdown_sender.subscribe(),
+                ))),
+            );
+        }
+        if let Some(admin) = &self.config.admin {
+            self.tasks.lock().await.insert(
+                Uuid::new_v4(),
+                Box::pin(tokio::spawn(crate::admin::serve(
+                    admin.clone(),
+                    self.state.clone(),
+                    self.bot_getter.clone(),
+                    self.action_sender.clone(),
+                    self.shutdown_sender.subscribe(),
+                ))),
+            );
+        }
+        self.load_plugins_task();
+        tokio::select! {
+            _ = self.handle_action() => {}
+            _ = crate::shutdown::signal() => {}
+        }
+        self.shutdown().await;
+    }
+
+    /// 关闭 Nonebot
+    ///
+    /// 依次停止接受新连接、广播 `NoneBotEvent::Shutdown`、在 `shutdown_timeout`
+    /// 内等待所有 `Plugin::shutdown` 完成、终止 Plugin 任务，最后关闭 WebSocket 连接。
+    pub async fn shutdown(&mut self) {
+        event!(Level::INFO, "Shutting down...");
+        self.shutdown_sender.send_replace(ShutdownPhase::Draining);
+        self.event_sender
+            .send(Event::Nonebot(NoneBotEvent::Shutdown))
+            .ok();
+
+        let timeout = Duration::from_secs(self.config.global.shutdown_timeout);
+        let hooks = self.plugins.values().map(|plugin| async move {
+            let plugin_info = plugin.plugin_info();
+            if tokio::time::timeout(timeout, plugin.shutdown())
+                .await
+                .is_err()
+            {
+                event!(
+                    Level::WARN,
+                    plugin = plugin_info.name,
+                    plugin_id = %plugin_info.id,
+                    "Plugin shutdown timeout."
+                );
+            }
+        });
+        futures_util::future::join_all(hooks).await;
+
+        for plugin in self.plugins.values() {
+            self.supervisor.abort(&plugin.plugin_info(), &self.health);
+        }
+        self.supervisor.abort_all();
+
+        let mut tasks = self.tasks.lock().await;
+
+        // 等待连接任务发送 Close 帧
+        self.shutdown_sender.send_replace(ShutdownPhase::Closing);
+        let closing = async {
+            for task in tasks.values_mut() {
+                task.await.ok();
+            }
+        };
+        if tokio::time::timeout(Duration::from_secs(3), closing)
+            .await
+            .is_err()
+        {
+            event!(Level::WARN, "WebSocket close timeout.");
+        }
+        for (_, task) in tasks.drain() {
+            task.abort();
+        }
+
+        self.state.clear();
+        event!(Level::DEBUG, "Shared state released");
+        event!(Level::INFO, "Nonebot stopped.");
     }
 }
 
 impl Default for Nonebot {
+    /// 读取配置失败时 panic，需要处理错误时使用 `Nonebot::with_config`
     fn default() -> Self {
-        let nb_config = crate::config::NoneBotConfig::load();
-        let (event_sender, _) = broadcast::channel(1024); // need largo cache when reconnect
-        let (action_sender, action_receiver) = tokio::sync::mpsc::channel(32);
-        let (bot_sender, bot_getter) = watch::channel(HashMap::new());
-        Nonebot {
-            bots: Default::default(),
-            config: nb_config,
-            event_sender,
-            action_sender,
-            action_receiver,
-            bot_sender,
-            bot_getter,
-            plugins: Default::default(),
-            tasks: Default::default(),
+        match crate::config::NoneBotConfig::load() {
+            Ok(config) => Self::with_config(config),
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[tokio::test]
+async fn unload_plugin_test() {
+    use crate::plugin::prelude::*;
+    use crate::supervisor::PluginStatus;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Debug)]
+    struct Slow;
+
+    #[crate::plugin(id = "8c1e2f3a-4b5d-4e6f-8a9b-0c1d2e3f4a5b")]
+    #[async_trait]
+    impl Plugin for Slow {
+        fn load(&self, _: crate::EventReceiver, _: crate::BotGetter) -> JoinHandle<()> {
+            tokio::spawn(std::future::pending())
+        }
+
+        async fn shutdown(&self) {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            SHUTDOWN.store(true, Ordering::SeqCst);
         }
     }
+
+    let dir = std::env::temp_dir().join(format!("nbrs-data-{}", Uuid::new_v4()));
+    let mut config = crate::config::NoneBotConfig::default();
+    config.global.data_dir = dir.clone();
+    let mut nonebot = Nonebot::with_config(config);
+    nonebot.add_plugin(Slow);
+    nonebot.load_plugins_task();
+    let id = Slow.plugin_info().id;
+
+    // 立即返回，关闭函数在独立任务中运行
+    nonebot.unload_plugin(&id);
+    let status = |nonebot: &Nonebot| {
+        nonebot
+            .plugin_health()
+            .into_iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, health)| health.status)
+    };
+    assert_eq!(status(&nonebot), Some(PluginStatus::Stopped));
+    assert!(!SHUTDOWN.load(Ordering::SeqCst));
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert!(SHUTDOWN.load(Ordering::SeqCst));
+    std::fs::remove_dir_all(dir).ok();
 }
@@ -3,48 +3,96 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use crate::state::StateMap;
+use crate::supervisor::RestartPolicy;
 use crate::{BotGetter, EventReceiver};
 
 /// Prelude for Plugin
 pub mod prelude {
     pub use super::Plugin;
     pub use super::PluginInfo;
+    pub use crate::async_trait;
     pub use crate::event::{Event, MessageEvent, NoneBotEvent};
     pub use crate::event::{SelfId, UserId};
     pub use crate::message::Message;
+    pub use crate::secret::Secret;
+    pub use crate::state::{State, StateMap};
+    pub use crate::stream::{DropPolicy, EventStream};
+    pub use crate::supervisor::RestartPolicy;
     pub use tokio::task::JoinHandle;
     pub use toml;
     pub use uuid::{uuid, Uuid};
 }
 
+/// 默认 Plugin 数据根目录
 pub static PLUGIN_DATA_DIR: &str = "data";
 
+/// Plugin 数据根目录，由 `global.data_dir` 设置，存放于 Nonebot 共享状态
+#[derive(Debug, Clone)]
+pub struct PluginDataDir(pub PathBuf);
+
+impl PluginDataDir {
+    /// 共享状态中的数据根目录，不存在时使用 `PLUGIN_DATA_DIR`
+    pub fn get(state: &StateMap) -> PathBuf {
+        match state.get::<PluginDataDir>() {
+            Some(dir) => (*dir).0.clone(),
+            None => PathBuf::from(PLUGIN_DATA_DIR),
+        }
+    }
+}
+
 /// A trait for nbrs plugins
+#[async_trait::async_trait]
 pub trait Plugin: std::fmt::Debug {
-    /// Plugin 初始化函数
-    fn init(&self) -> std::io::Result<()> {
-        create_dir_all(self.get_plugin_data_path())
+    /// Plugin 配置，读取自配置文件 `[plugins.<name>]`，无配置时为 `()`
+    ///
+    /// 使用 `#[plugin]` 时默认为 `()`
+    type Config: DeserializeOwned + Serialize + Default
+    where
+        Self: Sized;
+    /// Plugin 初始化函数，data_dir 为 `global.data_dir` 设置的数据根目录
+    fn init(&self, data_dir: &Path) -> std::io::Result<()> {
+        create_dir_all(self.get_plugin_data_path(data_dir))
     }
-    fn create_dir(&self, path: &Path) -> std::io::Result<()> {
-        let path = self.get_plugin_data_real_path(path);
+    fn create_dir(&self, data_dir: &Path, path: &Path) -> std::io::Result<()> {
+        let path = self.get_plugin_data_real_path(data_dir, path);
         create_dir_all(path)
     }
-    fn get_plugin_data_real_path(&self, path: &Path) -> PathBuf {
-        self.get_plugin_data_path().join(path)
+    fn get_plugin_data_real_path(&self, data_dir: &Path, path: &Path) -> PathBuf {
+        self.get_plugin_data_path(data_dir).join(path)
     }
-    fn get_plugin_data_path(&self) -> PathBuf {
-        Path::new(PLUGIN_DATA_DIR).join(self.plugin_info().id.to_string())
+    /// Plugin 数据目录 `<data_dir>/<id>`，data_dir 可由 `PluginDataDir::get` 获取
+    fn get_plugin_data_path(&self, data_dir: &Path) -> PathBuf {
+        data_dir.join(self.plugin_info().id.to_string())
+    }
+    /// 添加到 Nonebot 时调用，传入 Nonebot 共享状态
+    fn set_state(&mut self, _state: StateMap) {}
+    /// 添加到 Nonebot 时调用，传入读取的 Plugin 配置
+    fn set_config(&mut self, _config: Self::Config)
+    where
+        Self: Sized,
+    {
     }
     /// Plugin 启动函数，在 NoneBot 启动时调用一次，不应当阻塞
     fn load(&self, event_receiver: EventReceiver, bot_getter: BotGetter) -> JoinHandle<()>;
+    /// Plugin 任务退出后的重启策略，默认仅 panic 时重启
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::OnPanic
+    }
+    /// Plugin 关闭函数，在 Nonebot 关闭时调用一次
+    ///
+    /// 调用前已广播 `NoneBotEvent::Shutdown`，超出 `shutdown_timeout` 未完成时将被放弃，
+    /// 返回后 `load` 启动的任务将被终止。
+    async fn shutdown(&self) {}
     /// Plugin Name 用于注册 Plugin 时标识唯一性
     fn plugin_info(&self) -> PluginInfo;
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct PluginInfo {
     pub name: &'static str,
     pub author: &'static str,
@@ -52,3 +100,92 @@ pub struct PluginInfo {
     pub desc: &'static str,
     pub id: Uuid,
 }
+
+#[test]
+fn plugin_macro_test() {
+    #[derive(Debug)]
+    struct Dummy;
+
+    #[crate::plugin(id = "b86ad211-5bd5-42e8-8a74-4a40f37b78c2", desc = "dummy")]
+    impl Plugin for Dummy {
+        fn load(&self, _: EventReceiver, _: BotGetter) -> JoinHandle<()> {
+            tokio::spawn(async {})
+        }
+    }
+
+    let info = Dummy.plugin_info();
+    assert_eq!(info.name, "Dummy");
+    assert_eq!(info.desc, "dummy");
+    assert_eq!(info.id, uuid::uuid!("b86ad211-5bd5-42e8-8a74-4a40f37b78c2"));
+}
+
+#[test]
+fn plugin_config_test() {
+    #[derive(Debug, Default, PartialEq, serde::Deserialize, Serialize)]
+    struct DummyConfig {
+        key: String,
+        #[serde(default)]
+        timeout: u64,
+    }
+
+    #[derive(Debug, Default)]
+    struct Dummy {
+        config: DummyConfig,
+    }
+
+    #[crate::plugin(id = "e3b6c0a4-5a5c-4f0e-9d6a-3f1b2c4d5e6f")]
+    impl Plugin for Dummy {
+        type Config = DummyConfig;
+
+        fn load(&self, _: EventReceiver, _: BotGetter) -> JoinHandle<()> {
+            tokio::spawn(async {})
+        }
+
+        fn set_config(&mut self, config: DummyConfig) {
+            self.config = config;
+        }
+    }
+
+    let mut config = crate::config::NoneBotConfig::default();
+    let sections: toml::Table =
+        toml::from_str("[Dummy]\nkey = \"abc\"\n[Broken]\nkey = 1").unwrap();
+    config.plugins.extend(sections);
+    let mut dummy = Dummy::default();
+    dummy.set_config(config.plugin_config(dummy.plugin_info().name));
+    assert_eq!(
+        dummy.config,
+        DummyConfig {
+            key: "abc".to_string(),
+            timeout: 0
+        }
+    );
+    assert_eq!(
+        config.plugin_config::<DummyConfig>("Broken"),
+        DummyConfig::default()
+    );
+}
+
+#[tokio::test]
+async fn plugin_data_dir_test() {
+    #[derive(Debug)]
+    struct Dummy;
+
+    #[crate::plugin(id = "5f0c1d2e-8a3b-4c6d-9e7f-0a1b2c3d4e5f")]
+    impl Plugin for Dummy {
+        fn load(&self, _: EventReceiver, _: BotGetter) -> JoinHandle<()> {
+            tokio::spawn(async {})
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("nbrs-data-{}", Uuid::new_v4()));
+    let mut config = crate::config::NoneBotConfig::default();
+    config.global.data_dir = dir.clone();
+    let mut nonebot = crate::Nonebot::with_config(config);
+    nonebot.add_plugin(Dummy);
+    nonebot.load_plugins_task();
+
+    let id = Dummy.plugin_info().id.to_string();
+    assert!(dir.join(&id).is_dir());
+    assert!(!Path::new(PLUGIN_DATA_DIR).join(&id).exists());
+    std::fs::remove_dir_all(dir).ok();
+}
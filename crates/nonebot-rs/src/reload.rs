@@ -0,0 +1,280 @@
+//! 配置文件热重载
+//!
+//! 定时检查配置文件修改时间，发生变化时发送 `Action::ReloadConfig`。
+//! Bot 配置经 `Action::ChangeBotConfig` 更新，配置发生变化的 Plugin 将收到
+//! `NoneBotEvent::PluginConfigChanged`，连接等需重启生效的配置仅输出警告。
+
+use crate::config::NoneBotConfig;
+use crate::shutdown::{wait_phase, ShutdownPhase, ShutdownWatcher};
+use crate::{Action, ActionSender};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tracing::{event, Level};
+
+/// 配置文件检查间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 需重启生效的配置项前缀
+static RESTART_KEYS: [&str; 7] = [
+    "ws_server",
+    "record",
+    "replay",
+    "metrics",
+    "admin",
+    "global.hot_reload",
+    "global.data_dir",
+];
+
+/// 单个配置项的变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// 配置项路径，如 `global.superusers`
+    pub key: String,
+    pub old: Option<toml::Value>,
+    pub new: Option<toml::Value>,
+    /// 配置项类型为 `Secret`，输出时隐藏明文值
+    pub secret: bool,
+}
+
+impl Change {
+    /// 是否需要重启生效
+    pub fn restart_required(&self) -> bool {
+        let in_prefix =
+            |prefix: &str| self.key == prefix || self.key.starts_with(&format!("{}.", prefix));
+        RESTART_KEYS.iter().any(|prefix| in_prefix(prefix))
+            || (self.key.starts_with("bots.")
+                && (self.key.ends_with(".ws_server") || self.key.ends_with(".access_token")))
+    }
+
+    /// 所属 Plugin 配置名称
+    pub fn plugin(&self) -> Option<&str> {
+        self.key.strip_prefix("plugins.")?.split('.').next()
+    }
+}
+
+impl Change {
+    /// 用于输出的旧值，`Secret` 明文值被隐藏
+    pub fn old_text(&self) -> String {
+        self.show(&self.old)
+    }
+
+    /// 用于输出的新值，`Secret` 明文值被隐藏
+    pub fn new_text(&self) -> String {
+        self.show(&self.new)
+    }
+
+    fn show(&self, value: &Option<toml::Value>) -> String {
+        match value {
+            // `env:`、`file:` 引用不含密钥内容
+            Some(toml::Value::String(s))
+                if self.secret && !s.starts_with("env:") && !s.starts_with("file:") =>
+            {
+                "<redacted>".to_string()
+            }
+            Some(value) => value.to_string(),
+            None => "<none>".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.key,
+            self.old_text(),
+            self.new_text()
+        )
+    }
+}
+
+/// 比较两份配置，返回按配置项排序的变化
+pub fn diff(old: &NoneBotConfig, new: &NoneBotConfig) -> Vec<Change> {
+    let old_values = flatten(old);
+    let new_values = flatten(new);
+    let keys: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old_values.get(*key).map(|v| &v.0) != new_values.get(*key).map(|v| &v.0))
+        .map(|key| {
+            let (old_value, old_secret) = old_values.get(key).cloned().unzip();
+            let (new_value, new_secret) = new_values.get(key).cloned().unzip();
+            Change {
+                key: key.clone(),
+                old: old_value,
+                new: new_value,
+                secret: old_secret.unwrap_or(false)
+                    || new_secret.unwrap_or(false)
+                    || old.is_plugin_secret(key)
+                    || new.is_plugin_secret(key),
+            }
+        })
+        .collect()
+}
+
+/// 配置发生变化的 Plugin 名称
+pub fn changed_plugins(changes: &[Change]) -> BTreeSet<String> {
+    changes
+        .iter()
+        .filter_map(|change| change.plugin().map(str::to_string))
+        .collect()
+}
+
+fn flatten(config: &NoneBotConfig) -> BTreeMap<String, (toml::Value, bool)> {
+    match crate::secret::mark(|| toml::Value::try_from(config)) {
+        Ok(value) => flatten_value("", value),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// 展开配置为 `配置项路径 -> (值, 是否为 Secret)`
+///
+/// 需在 `secret::mark` 内序列化，`Secret` 序列化结果的标记在此移除
+pub(crate) fn flatten_value(
+    prefix: &str,
+    value: toml::Value,
+) -> BTreeMap<String, (toml::Value, bool)> {
+    fn walk(prefix: &str, value: toml::Value, out: &mut BTreeMap<String, (toml::Value, bool)>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    let key = match prefix {
+                        "" => key,
+                        prefix => format!("{}.{}", prefix, key),
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            toml::Value::String(s) if s.starts_with(crate::secret::MARKER) => {
+                let source = s[crate::secret::MARKER.len()..].to_string();
+                out.insert(prefix.to_string(), (toml::Value::String(source), true));
+            }
+            value => {
+                out.insert(prefix.to_string(), (value, false));
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    walk(prefix, value, &mut out);
+    out
+}
+
+/// 输出配置变化，需重启生效的配置项输出警告
+pub fn log_changes(changes: &[Change]) {
+    for change in changes {
+        let (key, old, new) = (&change.key, change.old_text(), change.new_text());
+        if change.restart_required() {
+            event!(
+                Level::WARN,
+                key,
+                old,
+                new,
+                "Config changed, restart required to apply"
+            );
+        } else {
+            event!(Level::INFO, key, old, new, "Config changed");
+        }
+    }
+}
+
+/// 监视配置文件，修改后发送 `Action::ReloadConfig`，进入 `Draining` 阶段后返回
+pub async fn watch(path: PathBuf, action_sender: ActionSender, mut shutdown: ShutdownWatcher) {
+    let modified = |path: &PathBuf| -> Option<SystemTime> { path.metadata().ok()?.modified().ok() };
+    let mut last = modified(&path);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    event!(Level::INFO, path = %path.display(), "Watching config file");
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = wait_phase(&mut shutdown, ShutdownPhase::Draining) => return,
+        }
+        let current = modified(&path);
+        if current.is_some() && current != last {
+            last = current;
+            event!(Level::INFO, "Config file {} modified", path.display());
+            if action_sender.send(Action::ReloadConfig).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[test]
+fn diff_test() {
+    let old = NoneBotConfig::default();
+    let mut new = old.clone();
+    new.global.superusers = vec!["123".to_string()];
+    new.ws_server.as_mut().unwrap().port = 8080;
+    new.plugins
+        .extend(toml::from_str::<toml::Table>("[moli]\ntrigger = \"小晴\"").unwrap());
+
+    let changes = diff(&old, &new);
+    let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        [
+            "global.superusers",
+            "plugins.moli.trigger",
+            "ws_server.port"
+        ]
+    );
+    let restart: Vec<bool> = changes.iter().map(Change::restart_required).collect();
+    assert_eq!(restart, [false, false, true]);
+    assert_eq!(changes[2].to_string(), "ws_server.port: 8088 -> 8080");
+    assert_eq!(
+        changed_plugins(&changes).into_iter().collect::<Vec<_>>(),
+        ["moli"]
+    );
+    assert!(diff(&new, &new).is_empty());
+
+    let change = |old: &str, new: &str| Change {
+        key: "plugins.moli.api_key".to_string(),
+        old: Some(toml::Value::String(old.to_string())),
+        new: Some(toml::Value::String(new.to_string())),
+        secret: true,
+    };
+    assert_eq!(
+        change("abc", "env:MOLI_KEY").to_string(),
+        "plugins.moli.api_key: <redacted> -> \"env:MOLI_KEY\""
+    );
+}
+
+#[test]
+fn diff_secret_test() {
+    #[derive(serde::Serialize, serde::Deserialize, Default)]
+    struct SaverConfig {
+        database_url: crate::Secret,
+        keyword: String,
+    }
+
+    let mut old = NoneBotConfig::default();
+    old.plugins.extend(
+        toml::from_str::<toml::Table>("[msgsaver]\ndatabase_url = \"a\"\nkeyword = \"b\"").unwrap(),
+    );
+    let _: SaverConfig = old.plugin_config("MsgSaver");
+    let mut new = old.clone();
+    new.admin = Some(crate::config::AdminConfig {
+        host: std::net::Ipv4Addr::LOCALHOST,
+        port: 8089,
+        access_token: crate::Secret::new("token"),
+    });
+    new.plugins.extend(
+        toml::from_str::<toml::Table>(
+            "[msgsaver]\ndatabase_url = \"postgres://u:p@h/db\"\nkeyword = \"c\"",
+        )
+        .unwrap(),
+    );
+
+    let changes: Vec<String> = diff(&old, &new).iter().map(Change::to_string).collect();
+    assert_eq!(
+        changes,
+        [
+            "admin.access_token: <none> -> <redacted>",
+            "admin.host: <none> -> \"127.0.0.1\"",
+            "admin.port: <none> -> 8089",
+            "plugins.msgsaver.database_url: <redacted> -> <redacted>",
+            "plugins.msgsaver.keyword: \"b\" -> \"c\"",
+        ]
+    );
+}
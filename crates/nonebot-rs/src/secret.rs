@@ -0,0 +1,135 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+
+/// 标记序列化结果中的密钥，仅用于识别配置项，不会写出
+pub(crate) const MARKER: &str = "\u{0}secret\u{0}";
+
+thread_local! {
+    static MARK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 执行 `f`，期间序列化的 `Secret` 以 `MARKER` 开头
+pub(crate) fn mark<R>(f: impl FnOnce() -> R) -> R {
+    MARK.with(|mark| mark.set(true));
+    let result = f();
+    MARK.with(|mark| mark.set(false));
+    result
+}
+
+/// 配置中的密钥
+///
+/// 配置值支持以下格式，读取配置时解析：
+/// - `env:NAME` 读取环境变量 `NAME`
+/// - `file:PATH` 读取文件内容并去除首尾空白，如 Docker secrets `file:/run/secrets/token`
+/// - 其他值视为明文
+///
+/// `Debug` 不输出密钥内容，序列化时输出引用而非解析后的值。
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret {
+    /// 配置中的原始值
+    source: String,
+    /// 解析后的值
+    value: String,
+}
+
+impl Secret {
+    /// 解析密钥引用
+    pub fn resolve(source: &str) -> Result<Self, String> {
+        let value = if let Some(name) = source.strip_prefix("env:") {
+            std::env::var(name)
+                .map_err(|e| format!("read environment variable `{}` fail: {}", name, e))?
+        } else if let Some(path) = source.strip_prefix("file:") {
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("read secret file `{}` fail: {}", path, e))?
+                .trim()
+                .to_string()
+        } else {
+            source.to_string()
+        };
+        Ok(Secret {
+            source: source.to_string(),
+            value,
+        })
+    }
+
+    /// 明文密钥
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Secret {
+            source: value.clone(),
+            value,
+        }
+    }
+
+    /// 获取密钥内容，不应输出至日志
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// 比较密钥，耗时与内容无关
+    pub fn matches(&self, other: &str) -> bool {
+        let (a, b) = (self.value.as_bytes(), other.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.value.is_empty() {
+            f.write_str("Secret(\"\")")
+        } else {
+            f.write_str("Secret(<redacted>)")
+        }
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if MARK.with(Cell::get) {
+            serializer.serialize_str(&format!("{}{}", MARKER, self.source))
+        } else {
+            serializer.serialize_str(&self.source)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // 环境变量覆盖的纯数字密钥会被解析为整数
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Source {
+            String(String),
+            Integer(i64),
+        }
+        let source = match Source::deserialize(deserializer)? {
+            Source::String(s) => s,
+            Source::Integer(i) => i.to_string(),
+        };
+        Secret::resolve(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[test]
+fn secret_test() {
+    let path = std::env::temp_dir().join(format!("nbrs-secret-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, "from-file\n").unwrap();
+    std::env::set_var("NBRS_SECRET_TEST", "from-env");
+
+    let file = Secret::resolve(&format!("file:{}", path.display())).unwrap();
+    let env = Secret::resolve("env:NBRS_SECRET_TEST").unwrap();
+    assert_eq!(file.expose(), "from-file");
+    assert_eq!(env.expose(), "from-env");
+    assert!(env.matches("from-env") && !env.matches("from-env2"));
+    assert_eq!(format!("{:?}", env), "Secret(<redacted>)");
+    assert_eq!(
+        toml::Value::try_from(&env).unwrap().as_str(),
+        Some("env:NBRS_SECRET_TEST")
+    );
+    assert!(Secret::resolve("env:NBRS_SECRET_MISSING").is_err());
+    std::fs::remove_file(path).ok();
+}
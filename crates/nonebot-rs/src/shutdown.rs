@@ -0,0 +1,50 @@
+use tokio::sync::watch;
+use tracing::{event, Level};
+
+/// Nonebot 运行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownPhase {
+    /// 正常运行
+    Running,
+    /// 停止接受新连接，等待 Plugin 完成 `shutdown`
+    Draining,
+    /// 关闭所有 WebSocket 连接
+    Closing,
+}
+
+/// 广播 Nonebot 运行阶段
+pub type ShutdownSender = watch::Sender<ShutdownPhase>;
+/// 接收 Nonebot 运行阶段
+pub type ShutdownWatcher = watch::Receiver<ShutdownPhase>;
+
+/// 等待进入指定阶段，Sender 已释放时永不返回
+pub async fn wait_phase(watcher: &mut ShutdownWatcher, phase: ShutdownPhase) {
+    if watcher.wait_for(|p| *p >= phase).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// 等待 SIGINT 或 SIGTERM（非 Unix 平台为 Ctrl-C）
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(e) => {
+                event!(Level::WARN, "Listen SIGTERM fail: {}", e);
+                tokio::signal::ctrl_c().await.ok();
+                return;
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => event!(Level::INFO, "Received SIGINT"),
+            _ = terminate.recv() => event!(Level::INFO, "Received SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.ok();
+        event!(Level::INFO, "Received Ctrl-C");
+    }
+}
@@ -0,0 +1,120 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+
+/// 按类型存储的共享状态表
+///
+/// Clone 后仍指向同一张表
+#[derive(Clone, Default)]
+pub struct StateMap {
+    inner: Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl std::fmt::Debug for StateMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateMap")
+            .field("len", &self.inner.read().unwrap().len())
+            .finish()
+    }
+}
+
+impl StateMap {
+    /// 新建空状态表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入状态，同类型状态将被替换
+    pub fn insert<T>(&self, value: T)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.inner
+            .write()
+            .unwrap()
+            .insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// 获取状态
+    pub fn get<T>(&self) -> Option<State<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        let value = self.inner.read().unwrap().get(&TypeId::of::<T>())?.clone();
+        value.downcast::<T>().ok().map(State)
+    }
+
+    /// 是否存在该类型状态
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.inner.read().unwrap().contains_key(&TypeId::of::<T>())
+    }
+
+    /// 移除状态
+    pub fn remove<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.inner
+            .write()
+            .unwrap()
+            .remove(&TypeId::of::<T>())
+            .is_some()
+    }
+
+    /// 将另一状态表中的状态并入当前表，已存在的类型不会被覆盖
+    pub fn merge(&self, other: &StateMap) {
+        if Arc::ptr_eq(&self.inner, &other.inner) {
+            return;
+        }
+        let other = other.inner.read().unwrap();
+        let mut inner = self.inner.write().unwrap();
+        for (k, v) in other.iter() {
+            inner.entry(*k).or_insert_with(|| v.clone());
+        }
+    }
+
+    /// 清空状态表
+    pub fn clear(&self) {
+        self.inner.write().unwrap().clear();
+    }
+}
+
+/// 共享状态
+///
+/// 由 `StateMap::get` 获取，也可作为 handler 函数参数
+#[derive(Debug)]
+pub struct State<T>(pub Arc<T>);
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(self.0.clone())
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[test]
+fn state_map_test() {
+    let shared = StateMap::new();
+    let local = StateMap::new();
+    local.insert(1u8);
+    local.insert(String::from("local"));
+    shared.insert(String::from("shared"));
+    shared.merge(&local);
+    assert_eq!(*shared.get::<u8>().unwrap(), 1);
+    assert_eq!(shared.get::<String>().unwrap().as_str(), "shared");
+    assert!(shared.remove::<u8>());
+    assert!(!shared.contains::<u8>());
+    shared.clear();
+    assert!(shared.get::<String>().is_none());
+}
@@ -0,0 +1,293 @@
+//! Plugin 事件流
+//!
+//! `broadcast::Receiver::recv` 在积压超过通道容量时返回 `RecvError::Lagged`，
+//! 直接使用 `while let Ok(event)` 的 Plugin 将因此永久停止接收事件。
+//! `EventStream` 在积压时记录跳过的事件数并继续接收，
+//! 慢速 Plugin 可使用 `EventStream::bounded` 设置独立的有界队列与丢弃策略。
+
+use crate::event::Event;
+use crate::EventReceiver;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+use tracing::{event, Level};
+
+/// 队列已满时的丢弃策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// 丢弃队列中最早的事件
+    DropOldest,
+    /// 丢弃新到达的事件
+    DropNewest,
+}
+
+/// 事件流背压统计
+#[derive(Debug, Default)]
+pub struct StreamMetrics {
+    /// 已交付 Plugin 的事件数
+    received: AtomicU64,
+    /// 因 broadcast 积压跳过的事件数
+    lagged: AtomicU64,
+    /// 因队列已满丢弃的事件数
+    dropped: AtomicU64,
+    /// 当前队列长度
+    queued: AtomicU64,
+    /// 队列长度峰值
+    peak: AtomicU64,
+}
+
+/// 事件流背压统计快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StreamStats {
+    pub name: String,
+    pub received: u64,
+    pub lagged: u64,
+    pub dropped: u64,
+    pub queued: u64,
+    pub peak: u64,
+}
+
+impl StreamMetrics {
+    fn set_queued(&self, len: usize) {
+        self.queued.store(len as u64, Ordering::Relaxed);
+        self.peak.fetch_max(len as u64, Ordering::Relaxed);
+    }
+
+    /// 生成快照
+    pub fn stats(&self, name: &str) -> StreamStats {
+        StreamStats {
+            name: name.to_string(),
+            received: self.received.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn registry() -> &'static Mutex<BTreeMap<String, Arc<StreamMetrics>>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<StreamMetrics>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// 所有事件流的背压统计，同名事件流共享统计
+pub fn stream_stats() -> Vec<StreamStats> {
+    registry()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, metrics)| metrics.stats(name))
+        .collect()
+}
+
+/// 积压容忍的 Plugin 事件流
+pub struct EventStream {
+    name: String,
+    metrics: Arc<StreamMetrics>,
+    inner: Inner,
+}
+
+enum Inner {
+    Direct(EventReceiver),
+    Queue {
+        queue: Arc<Queue>,
+        pump: JoinHandle<()>,
+    },
+}
+
+struct Queue {
+    events: Mutex<VecDeque<Event>>,
+    notify: Notify,
+    closed: AtomicBool,
+}
+
+impl std::fmt::Debug for EventStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventStream")
+            .field("name", &self.name)
+            .field("metrics", &self.metrics)
+            .finish()
+    }
+}
+
+impl EventStream {
+    /// 直接读取 broadcast 通道，积压时跳过并记录
+    pub fn new(name: &str, receiver: EventReceiver) -> Self {
+        EventStream {
+            name: name.to_string(),
+            metrics: register(name),
+            inner: Inner::Direct(receiver),
+        }
+    }
+
+    /// 使用独立的有界队列缓冲事件，队列已满时按 policy 丢弃
+    ///
+    /// 后台任务持续读取 broadcast 通道，Plugin 处理缓慢时不会拖累通道。
+    pub fn bounded(
+        name: &str,
+        mut receiver: EventReceiver,
+        capacity: usize,
+        policy: DropPolicy,
+    ) -> Self {
+        let capacity = capacity.max(1);
+        let metrics = register(name);
+        let queue = Arc::new(Queue {
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+        });
+        let pump = {
+            let queue = queue.clone();
+            let metrics = metrics.clone();
+            let name = name.to_string();
+            tokio::spawn(async move {
+                while let Some(event) = recv_lagged(&name, &metrics, &mut receiver).await {
+                    let mut events = queue.events.lock().unwrap();
+                    if events.len() >= capacity {
+                        let total = metrics.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                        event!(
+                            Level::WARN,
+                            stream = %name,
+                            "EventStream queue is full, {} events dropped",
+                            total
+                        );
+                        match policy {
+                            DropPolicy::DropOldest => {
+                                events.pop_front();
+                            }
+                            DropPolicy::DropNewest => continue,
+                        }
+                    }
+                    events.push_back(event);
+                    metrics.set_queued(events.len());
+                    drop(events);
+                    queue.notify.notify_one();
+                }
+                queue.closed.store(true, Ordering::Release);
+                queue.notify.notify_one();
+            })
+        };
+        EventStream {
+            name: name.to_string(),
+            metrics,
+            inner: Inner::Queue { queue, pump },
+        }
+    }
+
+    /// 接收下一个事件，通道关闭后返回 None
+    ///
+    /// 可安全用于 `tokio::select!`
+    pub async fn recv(&mut self) -> Option<Event> {
+        let event = match &mut self.inner {
+            Inner::Direct(receiver) => recv_lagged(&self.name, &self.metrics, receiver).await,
+            Inner::Queue { queue, .. } => loop {
+                let notified = queue.notify.notified();
+                {
+                    let mut events = queue.events.lock().unwrap();
+                    if let Some(event) = events.pop_front() {
+                        self.metrics.set_queued(events.len());
+                        break Some(event);
+                    }
+                }
+                if queue.closed.load(Ordering::Acquire) {
+                    break None;
+                }
+                notified.await;
+            },
+        };
+        if event.is_some() {
+            self.metrics.received.fetch_add(1, Ordering::Relaxed);
+        }
+        event
+    }
+
+    /// 事件流名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 当前背压统计
+    pub fn stats(&self) -> StreamStats {
+        self.metrics.stats(&self.name)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        if let Inner::Queue { pump, .. } = &self.inner {
+            pump.abort();
+        }
+    }
+}
+
+fn register(name: &str) -> Arc<StreamMetrics> {
+    registry()
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_default()
+        .clone()
+}
+
+async fn recv_lagged(
+    name: &str,
+    metrics: &StreamMetrics,
+    receiver: &mut EventReceiver,
+) -> Option<Event> {
+    loop {
+        match receiver.recv().await {
+            Ok(event) => return Some(event),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                let total = metrics.lagged.fetch_add(n, Ordering::Relaxed) + n;
+                crate::metrics::metrics().stream_lagged(name, n);
+                event!(
+                    Level::WARN,
+                    stream = name,
+                    "EventStream lagged, {} events skipped ({} total)",
+                    n,
+                    total
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[tokio::test]
+async fn event_stream_test() {
+    use crate::testing::MessageBuilder;
+
+    let (sender, _) = broadcast::channel(2);
+    let mut direct = EventStream::new("direct_test", sender.subscribe());
+    let mut oldest =
+        EventStream::bounded("oldest_test", sender.subscribe(), 2, DropPolicy::DropOldest);
+    let mut newest =
+        EventStream::bounded("newest_test", sender.subscribe(), 2, DropPolicy::DropNewest);
+    for i in 0..4 {
+        let event = MessageBuilder::private("1", &i.to_string()).build();
+        sender.send(Event::Message(event)).unwrap();
+        // 等待后台任务读取，避免 broadcast 积压
+        tokio::task::yield_now().await;
+    }
+    drop(sender);
+
+    fn text(event: Option<Event>) -> Option<String> {
+        match event {
+            Some(Event::Message(m)) => Some(m.get_raw_message().to_string()),
+            _ => None,
+        }
+    }
+    assert_eq!(text(direct.recv().await).as_deref(), Some("2"));
+    assert_eq!(direct.stats().lagged, 2);
+    assert_eq!(text(oldest.recv().await).as_deref(), Some("2"));
+    assert_eq!(text(newest.recv().await).as_deref(), Some("0"));
+    assert_eq!(text(newest.recv().await).as_deref(), Some("1"));
+    assert_eq!(text(newest.recv().await), None);
+    let stats = newest.stats();
+    assert_eq!((stats.received, stats.dropped, stats.peak), (2, 2, 2));
+    assert!(stream_stats().iter().any(|s| s.name == "oldest_test"));
+}
@@ -0,0 +1,354 @@
+use crate::plugin::PluginInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::task::{AbortHandle, JoinError, JoinHandle, JoinSet};
+use uuid::Uuid;
+
+/// 连续重启的最大等待时间
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Plugin 稳定运行超过该时间后重置重启等待时间
+const STABLE_TIME: i64 = 60;
+
+/// Plugin 退出后的重启策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestartPolicy {
+    /// 不重启
+    Never,
+    /// 仅 panic 时重启
+    OnPanic,
+    /// 任何退出均重启
+    Always,
+}
+
+/// Plugin 运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PluginStatus {
+    /// 运行中
+    Running,
+    /// 任务正常结束
+    Exited,
+    /// 任务 panic
+    Panicked,
+    /// 等待重启
+    Restarting,
+    /// 已被移除或关闭
+    Stopped,
+}
+
+impl std::fmt::Display for PluginStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PluginStatus::Running => "运行中",
+            PluginStatus::Exited => "已退出",
+            PluginStatus::Panicked => "已崩溃",
+            PluginStatus::Restarting => "重启中",
+            PluginStatus::Stopped => "已停止",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Plugin 健康状态
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginHealth {
+    pub name: &'static str,
+    pub status: PluginStatus,
+    /// 累计重启次数
+    pub restarts: u32,
+    /// 最近一次异常退出原因
+    pub last_error: Option<String>,
+    /// 进入当前状态的时间戳
+    pub since: i64,
+}
+
+/// 所有 Plugin 的健康状态
+///
+/// 存放于 Nonebot 共享状态，可通过 `State<PluginHealthTable>` 获取
+#[derive(Debug, Default)]
+pub struct PluginHealthTable {
+    inner: RwLock<BTreeMap<Uuid, (PluginInfo, PluginHealth)>>,
+}
+
+impl PluginHealthTable {
+    /// 获取 Plugin 健康状态
+    pub fn get(&self, id: &Uuid) -> Option<PluginHealth> {
+        self.inner
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|(_, health)| health.clone())
+    }
+
+    /// 获取所有 Plugin 健康状态
+    pub fn all(&self) -> Vec<(Uuid, PluginHealth)> {
+        self.inner
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, (_, health))| (*id, health.clone()))
+            .collect()
+    }
+
+    /// 获取所有 Plugin 信息及健康状态，包括已停止的 Plugin
+    pub fn plugins(&self) -> Vec<(PluginInfo, PluginHealth)> {
+        self.inner.read().unwrap().values().cloned().collect()
+    }
+
+    fn update<F>(&self, info: &PluginInfo, f: F) -> PluginHealth
+    where
+        F: FnOnce(&mut PluginHealth),
+    {
+        let mut inner = self.inner.write().unwrap();
+        let (_, health) = inner.entry(info.id).or_insert_with(|| {
+            let health = PluginHealth {
+                name: info.name,
+                status: PluginStatus::Running,
+                restarts: 0,
+                last_error: None,
+                since: crate::utils::timestamp(),
+            };
+            (*info, health)
+        });
+        f(health);
+        health.clone()
+    }
+
+    fn set_status(&self, info: &PluginInfo, status: PluginStatus) -> PluginHealth {
+        self.update(info, |health| {
+            health.status = status;
+            health.since = crate::utils::timestamp();
+        })
+    }
+}
+
+/// Supervisor 事件
+#[derive(Debug)]
+pub(crate) enum Exit {
+    /// Plugin 任务结束
+    Finished(Uuid, Result<(), JoinError>),
+    /// 重启等待结束
+    Restart(Uuid),
+}
+
+/// 基于 `JoinSet` 监视所有 Plugin 任务
+#[derive(Debug, Default)]
+pub(crate) struct Supervisor {
+    /// 任务结果附带派生代数，用于过滤已失效的退出事件
+    set: JoinSet<(u64, Exit)>,
+    /// 派生代数计数器，每次 watch 或安排重启递增
+    generation: u64,
+    /// 运行中的 Plugin 任务及其代数
+    aborts: HashMap<Uuid, (u64, AbortHandle)>,
+    /// 等待中的重启及其代数
+    pending: HashMap<Uuid, u64>,
+    /// 连续重启次数，用于计算等待时间
+    streaks: HashMap<Uuid, u32>,
+}
+
+impl Supervisor {
+    /// 监视 Plugin 任务
+    pub fn watch(&mut self, info: &PluginInfo, task: JoinHandle<()>, health: &PluginHealthTable) {
+        let id = info.id;
+        self.generation += 1;
+        let generation = self.generation;
+        if let Some((_, old)) = self.aborts.insert(id, (generation, task.abort_handle())) {
+            old.abort();
+        }
+        self.pending.remove(&id);
+        self.set
+            .spawn(async move { (generation, Exit::Finished(id, task.await)) });
+        health.set_status(info, PluginStatus::Running);
+    }
+
+    /// 终止 Plugin 任务，不会触发重启
+    pub fn abort(&mut self, info: &PluginInfo, health: &PluginHealthTable) {
+        if let Some((_, task)) = self.aborts.remove(&info.id) {
+            task.abort();
+        }
+        self.pending.remove(&info.id);
+        self.streaks.remove(&info.id);
+        health.set_status(info, PluginStatus::Stopped);
+    }
+
+    /// 停止监视 Plugin 任务但不终止，返回其 AbortHandle，不会触发重启
+    pub fn detach(&mut self, info: &PluginInfo, health: &PluginHealthTable) -> Option<AbortHandle> {
+        let task = self.aborts.remove(&info.id).map(|(_, task)| task);
+        self.pending.remove(&info.id);
+        self.streaks.remove(&info.id);
+        health.set_status(info, PluginStatus::Stopped);
+        task
+    }
+
+    /// 终止所有任务
+    pub fn abort_all(&mut self) {
+        for (_, (_, task)) in self.aborts.drain() {
+            task.abort();
+        }
+        self.pending.clear();
+        self.set.abort_all();
+    }
+
+    /// 等待下一个事件，无任务时返回 None
+    ///
+    /// 仅返回当前代数的事件，Plugin 移除后重新添加时旧任务的退出与重启会被忽略
+    pub async fn join_next(&mut self) -> Option<Exit> {
+        loop {
+            let Ok((generation, exit)) = self.set.join_next().await? else {
+                continue;
+            };
+            let current = match &exit {
+                Exit::Finished(id, _) => self.aborts.get(id).map(|(g, _)| *g),
+                Exit::Restart(id) => self.pending.remove(id),
+            };
+            if current == Some(generation) {
+                return Some(exit);
+            }
+        }
+    }
+
+    /// 记录 Plugin 退出，按策略安排重启，返回更新后的健康状态
+    pub fn exited(
+        &mut self,
+        info: &PluginInfo,
+        result: Result<(), JoinError>,
+        policy: RestartPolicy,
+        health: &PluginHealthTable,
+    ) -> PluginHealth {
+        self.aborts.remove(&info.id);
+        let (status, error) = match result {
+            Ok(()) => (PluginStatus::Exited, None),
+            Err(e) if e.is_panic() => (PluginStatus::Panicked, Some(panic_message(e))),
+            Err(e) => (PluginStatus::Exited, Some(e.to_string())),
+        };
+        let restart = match policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnPanic => status == PluginStatus::Panicked,
+            RestartPolicy::Always => true,
+        };
+
+        let stable = health
+            .get(&info.id)
+            .map(|h| crate::utils::timestamp() - h.since > STABLE_TIME)
+            .unwrap_or(true);
+        let streak = self.streaks.entry(info.id).or_default();
+        if stable {
+            *streak = 0;
+        }
+
+        let id = info.id;
+        if restart {
+            *streak += 1;
+            let delay = Duration::from_secs(1 << (*streak - 1).min(6)).min(MAX_BACKOFF);
+            self.generation += 1;
+            let generation = self.generation;
+            self.pending.insert(id, generation);
+            self.set.spawn(async move {
+                tokio::time::sleep(delay).await;
+                (generation, Exit::Restart(id))
+            });
+        }
+        health.update(info, |health| {
+            health.status = if restart {
+                PluginStatus::Restarting
+            } else {
+                status
+            };
+            health.since = crate::utils::timestamp();
+            if error.is_some() {
+                health.last_error = error;
+            }
+            if restart {
+                health.restarts += 1;
+            }
+        })
+    }
+}
+
+fn panic_message(e: JoinError) -> String {
+    let panic = e.into_panic();
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[tokio::test]
+async fn supervisor_test() {
+    let info = PluginInfo {
+        name: "Dummy",
+        author: "",
+        version: "",
+        desc: "",
+        id: Uuid::new_v4(),
+    };
+    let health = PluginHealthTable::default();
+    let mut supervisor = Supervisor::default();
+
+    supervisor.watch(&info, tokio::spawn(async { panic!("boom") }), &health);
+    let Some(Exit::Finished(id, result)) = supervisor.join_next().await else {
+        panic!("plugin exit not detected");
+    };
+    assert_eq!(id, info.id);
+    let h = supervisor.exited(&info, result, RestartPolicy::OnPanic, &health);
+    assert_eq!(h.status, PluginStatus::Restarting);
+    assert_eq!(h.last_error.as_deref(), Some("boom"));
+    assert!(matches!(
+        supervisor.join_next().await,
+        Some(Exit::Restart(_))
+    ));
+
+    supervisor.watch(&info, tokio::spawn(std::future::pending()), &health);
+    assert_eq!(health.get(&info.id).unwrap().status, PluginStatus::Running);
+    supervisor.abort(&info, &health);
+    assert!(supervisor.join_next().await.is_none());
+    let h = health.get(&info.id).unwrap();
+    assert_eq!((h.status, h.restarts), (PluginStatus::Stopped, 1));
+}
+
+#[tokio::test]
+async fn supervisor_stale_exit_test() {
+    let info = PluginInfo {
+        name: "Dummy",
+        author: "",
+        version: "",
+        desc: "",
+        id: Uuid::new_v4(),
+    };
+    let health = PluginHealthTable::default();
+    let mut supervisor = Supervisor::default();
+
+    // 旧任务被移除后才结束，随后同一 Plugin 重新添加
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    supervisor.watch(
+        &info,
+        tokio::spawn(async move {
+            let _ = rx.await;
+        }),
+        &health,
+    );
+    supervisor.aborts.remove(&info.id);
+    supervisor.watch(&info, tokio::spawn(std::future::pending()), &health);
+    tx.send(()).unwrap();
+
+    let stale = tokio::time::timeout(Duration::from_millis(100), supervisor.join_next()).await;
+    assert!(stale.is_err(), "stale exit returned: {:?}", stale);
+    assert!(supervisor.aborts.contains_key(&info.id));
+    assert_eq!(health.get(&info.id).unwrap().status, PluginStatus::Running);
+
+    // 等待中的重启在 Plugin 重新添加后失效
+    supervisor.abort(&info, &health);
+    supervisor.watch(&info, tokio::spawn(async {}), &health);
+    let Some(Exit::Finished(_, result)) = supervisor.join_next().await else {
+        panic!("plugin exit not detected");
+    };
+    supervisor.exited(&info, result, RestartPolicy::Always, &health);
+    supervisor.abort(&info, &health);
+    supervisor.watch(&info, tokio::spawn(std::future::pending()), &health);
+    supervisor.abort(&info, &health);
+    assert!(supervisor.join_next().await.is_none());
+}
@@ -0,0 +1,345 @@
+//! 离线测试工具
+//!
+//! 使用捕获 `api_sender` 的 `TestBot` 驱动 Plugin（包括 `Matchers`），
+//! 注入构造的 Event 并断言 Bot 发出的 Api 调用。
+//!
+//! ```ignore
+//! let mut bot = TestBot::new();
+//! bot.load(matchers);
+//! bot.send_message(MessageBuilder::private("10", "echo hi").build());
+//! assert_eq!(bot.next_reply_text().await.as_deref(), Some(" hi"));
+//! ```
+
+use crate::api::Api;
+use crate::api_resp::ApiResp;
+use crate::config::BotConfig;
+use crate::event::{
+    Event, GroupMessageEvent, GroupSender, MessageEvent, PrivateMessageEvent, PrivateSender,
+};
+use crate::message::Message;
+use crate::state::StateMap;
+use crate::{
+    Action, ActionReceiver, ApiChannelItem, Bot, BotSender, EventReceiver, EventSender, Plugin,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// TestBot 默认 bot id
+pub const TEST_BOT_ID: &str = "10000";
+/// 等待 Api 调用默认超时时间
+pub const DEFAULT_WAIT: Duration = Duration::from_secs(1);
+
+/// 离线测试 Bot
+pub struct TestBot {
+    /// 被测 Plugin 使用的 Bot
+    pub bot: Bot,
+    /// 等待 Api 调用超时时间
+    pub wait: Duration,
+    api_receiver: mpsc::Receiver<ApiChannelItem>,
+    event_sender: EventSender,
+    resp_sender: watch::Sender<ApiResp>,
+    bot_sender: BotSender,
+    action_receiver: ActionReceiver,
+    state: StateMap,
+    /// 临时 Plugin 数据根目录，drop 时删除
+    data_dir: PathBuf,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Default for TestBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestBot {
+    /// 新建默认配置的 TestBot，无命令起始符与 superuser
+    ///
+    /// Plugin 数据写入临时目录，不会写入工作目录下的 `data`
+    pub fn new() -> Self {
+        let mut config = BotConfig::default();
+        config.bot_id = TEST_BOT_ID.to_string();
+        Self::with_config(config)
+    }
+
+    /// 使用指定配置新建 TestBot
+    pub fn with_config(config: BotConfig) -> Self {
+        let (api_sender, api_receiver) = mpsc::channel(64);
+        let (action_sender, action_receiver) = mpsc::channel(32);
+        let (resp_sender, resp_watcher) = watch::channel(ApiResp {
+            status: String::default(),
+            retcode: 0,
+            data: crate::api_resp::RespData::None,
+            echo: String::default(),
+        });
+        let (event_sender, _) = broadcast::channel(64);
+        let bot = Bot::new(
+            config.bot_id.clone(),
+            config,
+            api_sender,
+            action_sender,
+            resp_watcher,
+        );
+        let mut bots = HashMap::new();
+        bots.insert(bot.bot_id.clone(), bot.clone());
+        let (bot_sender, _) = watch::channel(bots);
+        let state = StateMap::new();
+        state.insert(crate::supervisor::PluginHealthTable::default());
+        state.insert(crate::builtin::matcher::matchers::MatcherTable::default());
+        let data_dir = std::env::temp_dir().join(format!("nbrs-test-{}", uuid::Uuid::new_v4()));
+        state.insert(crate::plugin::PluginDataDir(data_dir.clone()));
+        TestBot {
+            bot,
+            wait: DEFAULT_WAIT,
+            api_receiver,
+            event_sender,
+            resp_sender,
+            bot_sender,
+            action_receiver,
+            state,
+            data_dir,
+            tasks: vec![],
+        }
+    }
+
+    /// 加载 Plugin，Plugin 将接收此后注入的 Event
+    ///
+    /// 与 Nonebot 相同，Plugin 共享包含 `MatcherTable` 的状态
+    pub fn load<P>(&mut self, mut plugin: P) -> &mut Self
+    where
+        P: Plugin,
+    {
+        plugin.set_state(self.state.clone());
+        plugin.init(&self.data_dir).ok();
+        let task = plugin.load(self.subscribe(), self.bot_sender.subscribe());
+        self.tasks.push(task);
+        self
+    }
+
+    /// Plugin 共享状态，需在 `load` 前插入
+    pub fn state(&self) -> &StateMap {
+        &self.state
+    }
+
+    /// 订阅注入的 Event
+    pub fn subscribe(&self) -> EventReceiver {
+        self.event_sender.subscribe()
+    }
+
+    /// 注入 Event
+    pub fn send(&self, event: Event) {
+        self.event_sender.send(event).ok();
+    }
+
+    /// 注入 MessageEvent
+    pub fn send_message(&self, event: MessageEvent) {
+        self.send(Event::Message(event));
+    }
+
+    /// 模拟 Onebot 返回 ApiResp，用于 `call_api_resp`
+    pub fn respond(&self, resp: ApiResp) {
+        self.resp_sender.send(resp).ok();
+    }
+
+    /// 等待下一个 Api 调用，超时返回 None
+    pub async fn next_api(&mut self) -> Option<Api> {
+        loop {
+            match tokio::time::timeout(self.wait, self.api_receiver.recv()).await {
+                Ok(Some(ApiChannelItem::Api(api))) => return Some(api),
+                Ok(Some(_)) => continue,
+                _ => return None,
+            }
+        }
+    }
+
+    /// 等待下一条发送的消息，跳过其他 Api 调用
+    pub async fn next_reply(&mut self) -> Option<Vec<Message>> {
+        while let Some(api) = self.next_api().await {
+            match api {
+                Api::SendPrivateMsg { params, .. } => return Some(params.message),
+                Api::SendGroupMsg { params, .. } => return Some(params.message),
+                Api::SendMsg { params, .. } => return Some(params.message),
+                _ => continue,
+            }
+        }
+        None
+    }
+
+    /// 等待下一条发送的消息，仅拼接其中的文本
+    pub async fn next_reply_text(&mut self) -> Option<String> {
+        self.next_reply().await.map(|msg| {
+            msg.into_iter()
+                .filter_map(|m| match m {
+                    Message::Text { text } => Some(text),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// 等待下一个发送至 Nonebot 的 Action，超时返回 None
+    pub async fn next_action(&mut self) -> Option<Action> {
+        tokio::time::timeout(self.wait, self.action_receiver.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// 断言等待时间内没有 Api 调用
+    pub async fn assert_no_api(&mut self) {
+        if let Some(api) = self.next_api().await {
+            panic!("unexpected api call: {:?}", api);
+        }
+    }
+}
+
+impl Drop for TestBot {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        std::fs::remove_dir_all(&self.data_dir).ok();
+    }
+}
+
+/// MessageEvent 构造器
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    self_id: String,
+    user_id: String,
+    group_id: Option<String>,
+    nickname: String,
+    role: String,
+    message: Vec<Message>,
+    raw_message: String,
+}
+
+impl MessageBuilder {
+    fn new(group_id: Option<&str>, user_id: &str, text: &str) -> Self {
+        MessageBuilder {
+            self_id: TEST_BOT_ID.to_string(),
+            user_id: user_id.to_string(),
+            group_id: group_id.map(|g| g.to_string()),
+            nickname: user_id.to_string(),
+            role: "member".to_string(),
+            message: vec![Message::text(text.to_string())],
+            raw_message: text.to_string(),
+        }
+    }
+
+    /// 私聊文本消息
+    pub fn private(user_id: &str, text: &str) -> Self {
+        Self::new(None, user_id, text)
+    }
+
+    /// 群文本消息
+    pub fn group(group_id: &str, user_id: &str, text: &str) -> Self {
+        Self::new(Some(group_id), user_id, text)
+    }
+
+    /// 设置接收消息的 bot id
+    pub fn self_id(mut self, self_id: &str) -> Self {
+        self.self_id = self_id.to_string();
+        self
+    }
+
+    /// 设置发送者昵称
+    pub fn nickname(mut self, nickname: &str) -> Self {
+        self.nickname = nickname.to_string();
+        self
+    }
+
+    /// 设置发送者群角色 owner|admin|member
+    pub fn role(mut self, role: &str) -> Self {
+        self.role = role.to_string();
+        self
+    }
+
+    /// 设置消息段，raw_message 保持不变
+    pub fn message(mut self, message: Vec<Message>) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// 构造 MessageEvent
+    pub fn build(self) -> MessageEvent {
+        let time = crate::utils::timestamp();
+        match self.group_id {
+            Some(group_id) => MessageEvent::Group(GroupMessageEvent {
+                time,
+                self_id: self.self_id,
+                sub_type: "normal".to_string(),
+                message_id: 0,
+                group_id,
+                user_id: self.user_id.clone(),
+                anonymous: None,
+                message: self.message,
+                raw_message: self.raw_message,
+                font: 0,
+                sender: GroupSender {
+                    user_id: self.user_id,
+                    nickname: self.nickname,
+                    card: String::default(),
+                    sex: "unknown".to_string(),
+                    age: 0,
+                    area: String::default(),
+                    level: String::default(),
+                    role: self.role,
+                    title: String::default(),
+                },
+            }),
+            None => MessageEvent::Private(PrivateMessageEvent {
+                time,
+                self_id: self.self_id,
+                sub_type: "friend".to_string(),
+                message_id: 0,
+                user_id: self.user_id.clone(),
+                message: self.message,
+                raw_message: self.raw_message,
+                font: 0,
+                sender: PrivateSender {
+                    user_id: self.user_id,
+                    nickname: self.nickname,
+                    sex: "unknown".to_string(),
+                    age: 0,
+                },
+            }),
+        }
+    }
+}
+
+#[tokio::test]
+async fn testing_test() {
+    use crate::builtin::matcher::prelude::*;
+
+    async fn greet(event: MessageEvent, matcher: Matcher<MessageEvent>) {
+        let name = matcher
+            .request_message(Some(&event), Some("你是谁？"))
+            .await;
+        if let Some(name) = name {
+            matcher.send_text(&format!("你好，{}", name)).await;
+        }
+    }
+
+    let mut matchers = crate::Matchers::new_empty();
+    matchers.add_message_matcher(crate::builtin::echo::echo());
+    matchers.add_message_matcher(Matcher::new(
+        "Greet",
+        FnHandler::new(greet).command(&["greet"]),
+    ));
+
+    let mut bot = TestBot::new();
+    bot.load(matchers);
+
+    bot.send_message(MessageBuilder::private("1", "echo hi").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some(" hi"));
+
+    bot.send_message(MessageBuilder::group("2", "1", "greet").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("你是谁？"));
+    bot.send_message(MessageBuilder::group("2", "1", "Ame").build());
+    assert_eq!(bot.next_reply_text().await.as_deref(), Some("你好，Ame"));
+    bot.assert_no_api().await;
+}
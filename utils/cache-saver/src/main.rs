@@ -3,10 +3,11 @@ use colored::*;
 use futures_util::StreamExt;
 use sha256::digest;
 use sqlx::postgres::PgPoolOptions;
-use tokio::{io::AsyncWriteExt, time};
+use tokio::{io::AsyncWriteExt, signal, time};
 
 static BASE_PATH: &str = "cache/";
 static STATUS_FILE: &str = "cache/cache_status";
+static STATUS_TMP_FILE: &str = "cache/cache_status.tmp";
 static REQ_LENGTH: i32 = 20;
 
 #[tokio::main]
@@ -20,10 +21,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tokio::fs::create_dir_all(BASE_PATH).await?;
 
+    // 仅在两次任务之间退出，任务进行中收到的信号将在任务结束后处理
+    #[cfg(unix)]
+    let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+    #[cfg(unix)]
+    let mut interrupt = signal::unix::signal(signal::unix::SignalKind::interrupt())?;
     loop {
-        interval.tick().await;
+        #[cfg(unix)]
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = terminate.recv() => break,
+            _ = interrupt.recv() => break,
+        }
+        #[cfg(not(unix))]
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = signal::ctrl_c() => break,
+        }
         task(&pool).await?;
     }
+
+    println!("{}", "Shutting down...".yellow());
+    pool.close().await;
+    Ok(())
 }
 
 async fn task(pool: &sqlx::PgPool) -> Result<(), Box<dyn std::error::Error>> {
@@ -89,7 +109,9 @@ async fn get_last_status() -> i32 {
     }
 }
 
+/// 先写入临时文件再重命名，避免状态文件写入一半
 async fn write_status(s: i32) -> tokio::io::Result<()> {
-    tokio::fs::write(STATUS_FILE, s.to_string()).await?;
+    tokio::fs::write(STATUS_TMP_FILE, s.to_string()).await?;
+    tokio::fs::rename(STATUS_TMP_FILE, STATUS_FILE).await?;
     Ok(())
 }
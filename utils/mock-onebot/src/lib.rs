@@ -0,0 +1,291 @@
+//! 模拟 OneBot v11 实现，用于 nonebot-rs 端到端测试
+//!
+//! 按剧本向 nonebot-rs 发送事件，以预设 ApiResp 应答 Api 调用，并记录收到的全部调用。
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Notify};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse, Request as ServerRequest, Response as ServerResponse,
+};
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{event, Level};
+
+pub mod scenario;
+
+pub use scenario::{Expect, MessageStep, Scenario, Step};
+
+/// Mock 运行错误
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// 收到的 Api 调用
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedCall {
+    pub action: String,
+    pub params: Value,
+    pub echo: Value,
+}
+
+/// 剧本运行结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Report {
+    /// 按收到顺序记录的 Api 调用
+    pub calls: Vec<RecordedCall>,
+    /// 未满足的断言
+    pub failures: Vec<String>,
+}
+
+impl Report {
+    /// 所有断言是否均满足
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 模拟 OneBot v11 实现
+#[derive(Debug, Clone)]
+pub struct MockOneBot {
+    scenario: Arc<Scenario>,
+    /// 剧本结束后继续记录 Api 调用的时间
+    pub linger: Duration,
+}
+
+impl MockOneBot {
+    pub fn new(scenario: Scenario) -> Self {
+        MockOneBot {
+            scenario: Arc::new(scenario),
+            linger: Duration::from_millis(500),
+        }
+    }
+
+    /// 反向 WebSocket：连接 nonebot-rs 的 revs_ws 服务，连接失败时在 retry 时间内重试
+    pub async fn connect(&self, url: &str, retry: Duration) -> Result<Report, Error> {
+        let deadline = tokio::time::Instant::now() + retry;
+        loop {
+            let mut request = url.into_client_request()?;
+            let headers = request.headers_mut();
+            headers.insert("X-Self-ID", HeaderValue::from_str(&self.scenario.self_id)?);
+            headers.insert("X-Client-Role", HeaderValue::from_static("Universal"));
+            headers.insert("User-Agent", HeaderValue::from_static("MockOneBot/11"));
+            if !self.scenario.access_token.is_empty() {
+                headers.insert(
+                    "Authorization",
+                    HeaderValue::from_str(&format!("Bearer {}", self.scenario.access_token))?,
+                );
+            }
+            match tokio_tungstenite::connect_async(request).await {
+                Ok((ws, _)) => return Ok(self.run(ws).await),
+                Err(e) if tokio::time::Instant::now() < deadline => {
+                    event!(Level::WARN, url, "Connect fail: {}, retrying", e);
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// 正向 WebSocket：监听地址并等待 nonebot-rs 连接
+    pub async fn listen(&self, addr: SocketAddr) -> Result<Report, Error> {
+        let listener = TcpListener::bind(addr).await?;
+        self.accept(listener).await
+    }
+
+    /// 正向 WebSocket：在已绑定的 listener 上等待一个连接
+    pub async fn accept(&self, listener: TcpListener) -> Result<Report, Error> {
+        let (stream, _) = listener.accept().await?;
+        let access_token = self.scenario.access_token.clone();
+        #[allow(clippy::result_large_err)]
+        let callback = |req: &ServerRequest, resp: ServerResponse| {
+            let auth = req
+                .headers()
+                .get("Authorization")
+                .and_then(|a| a.to_str().ok())
+                .unwrap_or_default();
+            if access_token.is_empty() || auth == format!("Bearer {}", access_token) {
+                Ok(resp)
+            } else {
+                let mut err = ErrorResponse::new(Some("Unauthorized".to_string()));
+                *err.status_mut() = tokio_tungstenite::tungstenite::http::StatusCode::UNAUTHORIZED;
+                Err(err)
+            }
+        };
+        let ws = tokio_tungstenite::accept_hdr_async(stream, callback).await?;
+        Ok(self.run(ws).await)
+    }
+
+    /// 在已建立的 WebSocket 上运行剧本
+    pub async fn run<S>(&self, ws: WebSocketStream<S>) -> Report
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut sink, mut stream) = ws.split();
+        let calls: Arc<Mutex<Vec<RecordedCall>>> = Default::default();
+        let notify = Arc::new(Notify::new());
+        let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+
+        let writer = tokio::spawn(async move {
+            while let Some(text) = receiver.recv().await {
+                if sink.send(Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+            sink.close().await.ok();
+        });
+
+        let reader = {
+            let calls = calls.clone();
+            let notify = notify.clone();
+            let sender = sender.clone();
+            let scenario = self.scenario.clone();
+            tokio::spawn(async move {
+                while let Some(Ok(msg)) = stream.next().await {
+                    let Message::Text(text) = msg else {
+                        continue;
+                    };
+                    let Ok(data) = serde_json::from_str::<Value>(&text) else {
+                        event!(Level::WARN, frame = %text, "Unknown frame");
+                        continue;
+                    };
+                    let Some(action) = data["action"].as_str() else {
+                        continue;
+                    };
+                    let call = RecordedCall {
+                        action: action.to_string(),
+                        params: data["params"].clone(),
+                        echo: data["echo"].clone(),
+                    };
+                    let resp = scenario.response(action, &call.echo);
+                    calls.lock().unwrap().push(call);
+                    notify.notify_waiters();
+                    sender.send(resp.to_string()).ok();
+                }
+            })
+        };
+
+        let mut failures = vec![];
+        let mut cursor = 0;
+        let mut message_id = 0;
+        for step in &self.scenario.steps {
+            match step {
+                Step::Event(event) => {
+                    let event = scenario::fill_event(event.clone(), &self.scenario.self_id);
+                    sender.send(event.to_string()).ok();
+                }
+                Step::Message(message) => {
+                    message_id += 1;
+                    let event = message.to_event(&self.scenario.self_id, message_id);
+                    sender.send(event.to_string()).ok();
+                }
+                Step::Sleep(ms) => tokio::time::sleep(Duration::from_millis(*ms)).await,
+                Step::Expect(expect) => match wait_call(&calls, &notify, expect, cursor).await {
+                    Some(index) => cursor = index + 1,
+                    None => failures.push(format!("expect {:?} timeout", expect)),
+                },
+                Step::ExpectNone(expect) => {
+                    if let Some(index) = wait_call(&calls, &notify, expect, cursor).await {
+                        let call = calls.lock().unwrap()[index].clone();
+                        failures.push(format!("unexpected {:?}", call));
+                        cursor = index + 1;
+                    }
+                }
+            }
+        }
+
+        tokio::time::sleep(self.linger).await;
+        drop(sender);
+        reader.abort();
+        writer.await.ok();
+
+        let calls = calls.lock().unwrap().clone();
+        Report { calls, failures }
+    }
+}
+
+/// 等待 cursor 之后第一个满足断言的 Api 调用，返回其序号
+async fn wait_call(
+    calls: &Mutex<Vec<RecordedCall>>,
+    notify: &Notify,
+    expect: &Expect,
+    cursor: usize,
+) -> Option<usize> {
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(expect.timeout_ms);
+    loop {
+        let notified = notify.notified();
+        if let Some(index) = calls
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .skip(cursor)
+            .find(|(_, call)| expect.matches(call))
+            .map(|(index, _)| index)
+        {
+            return Some(index);
+        }
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            return None;
+        }
+    }
+}
+
+#[tokio::test]
+async fn mock_onebot_test() {
+    use nonebot_rs::config::{BotConfig, NoneBotConfig};
+
+    let scenario: Scenario = serde_yaml::from_str(
+        r#"
+steps:
+  - event: { post_type: meta_event, meta_event_type: lifecycle, sub_type: connect }
+  - sleep: 200
+  - message: { user_id: "1", text: "/echo hi" }
+  - expect: { action: send_private_msg, params: { user_id: 1 }, text: " hi" }
+  - message: { user_id: "1", text: "/ping" }
+  - expect_none: { action: send_private_msg, timeout_ms: 300 }
+  - expect: { action: get_login_info, timeout_ms: 200 }
+"#,
+    )
+    .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut mock = MockOneBot::new(scenario);
+    mock.linger = Duration::from_millis(50);
+    let mock = tokio::spawn(async move { mock.accept(listener).await.unwrap() });
+
+    // nonebot-rs 以正向 WebSocket 连接 Mock
+    let data_dir = std::env::temp_dir().join(format!("mock-onebot-{}", std::process::id()));
+    let mut config = NoneBotConfig::default();
+    config.ws_server = None;
+    config.global.hot_reload = false;
+    config.global.data_dir = data_dir.clone();
+    let mut bot = BotConfig::default();
+    bot.ws_server = format!("ws://{}/", addr);
+    config.bots = Some([("10000".to_string(), bot)].into());
+    let mut nonebot = nonebot_rs::Nonebot::with_config(config);
+    let mut matchers = nonebot_rs::Matchers::new_empty();
+    matchers.add_message_matcher(nonebot_rs::builtin::echo::echo());
+    nonebot.add_plugin(matchers);
+    let action_sender = nonebot.action_sender();
+    let nonebot = tokio::spawn(async move { nonebot.run().await });
+
+    let report = mock.await.unwrap();
+    action_sender
+        .send(nonebot_rs::Action::Shutdown)
+        .await
+        .unwrap();
+    nonebot.await.unwrap();
+    std::fs::remove_dir_all(data_dir).ok();
+
+    let actions: Vec<&str> = report.calls.iter().map(|c| c.action.as_str()).collect();
+    assert_eq!(actions, ["send_private_msg"]);
+    assert_eq!(report.failures.len(), 1, "{:?}", report.failures);
+    assert!(report.failures[0].contains("get_login_info"));
+}
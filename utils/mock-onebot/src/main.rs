@@ -0,0 +1,101 @@
+use clap::Parser;
+use colored::*;
+use mock_onebot::{MockOneBot, Scenario};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 按剧本模拟 OneBot 实现端
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 剧本文件路径（yaml 或 json）
+    scenario: PathBuf,
+    /// 作为客户端连接 Bot 的 WebSocket 地址
+    #[arg(long, required_unless_present = "listen", conflicts_with = "listen")]
+    connect: Option<String>,
+    /// 作为服务端监听的地址
+    #[arg(long)]
+    listen: Option<SocketAddr>,
+    /// 将 API 调用记录写入该文件
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// 连接失败时的重试时长（秒）
+    #[arg(long, default_value_t = 30)]
+    retry: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let cli = Cli::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mock = MockOneBot::new(Scenario::load(&cli.scenario)?);
+    let report = match (cli.connect, cli.listen) {
+        (Some(url), _) => mock.connect(&url, Duration::from_secs(cli.retry)).await?,
+        (None, Some(addr)) => mock.listen(addr).await?,
+        (None, None) => unreachable!("clap requires --connect or --listen"),
+    };
+
+    for call in &report.calls {
+        println!("{} {}", call.action.bright_cyan(), call.params);
+    }
+    if let Some(record) = cli.record {
+        std::fs::write(record, serde_json::to_string_pretty(&report)?)?;
+    }
+    if report.is_ok() {
+        println!("{}", "Scenario passed".green());
+        Ok(())
+    } else {
+        for failure in &report.failures {
+            eprintln!("{}", failure.red());
+        }
+        std::process::exit(1);
+    }
+}
+
+#[test]
+fn cli_test() {
+    use clap::CommandFactory;
+    Cli::command().debug_assert();
+
+    let cli = Cli::parse_from(["mock-onebot", "s.yaml", "--listen", "127.0.0.1:8080"]);
+    assert_eq!(cli.listen, Some("127.0.0.1:8080".parse().unwrap()));
+    assert_eq!(cli.retry, 30);
+
+    let cli = Cli::parse_from([
+        "mock-onebot",
+        "s.yaml",
+        "--connect",
+        "ws://x",
+        "--retry",
+        "5",
+    ]);
+    assert_eq!((cli.connect.as_deref(), cli.retry), (Some("ws://x"), 5));
+
+    for args in [
+        &["mock-onebot", "s.yaml"][..],
+        &[
+            "mock-onebot",
+            "s.yaml",
+            "--connect",
+            "ws://x",
+            "--listen",
+            "127.0.0.1:8080",
+        ],
+        &[
+            "mock-onebot",
+            "s.yaml",
+            "--connect",
+            "ws://x",
+            "--retry",
+            "abc",
+        ],
+        &["mock-onebot", "s.yaml", "--connect", "ws://x", "--unknown"],
+        &["mock-onebot", "s.yaml", "extra.yaml", "--connect", "ws://x"],
+    ] {
+        assert!(Cli::try_parse_from(args).is_err(), "{:?}", args);
+    }
+}
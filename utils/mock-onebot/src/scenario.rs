@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+fn default_self_id() -> String {
+    "10000".to_string()
+}
+
+fn default_timeout() -> u64 {
+    3000
+}
+
+/// 测试剧本
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Scenario {
+    /// 模拟的 bot QQ 号
+    #[serde(default = "default_self_id")]
+    pub self_id: String,
+    /// Authorization 使用的 access_token
+    #[serde(default)]
+    pub access_token: String,
+    /// 按 action 返回的 ApiResp（不含 echo），未配置的 action 返回 `{"status":"ok","retcode":0,"data":null}`
+    #[serde(default)]
+    pub responses: HashMap<String, Value>,
+    /// 依序执行的步骤，YAML 中写作 `- message: {...}` 形式
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub steps: Vec<Step>,
+}
+
+/// 剧本步骤
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// 发送原始 OneBot 事件，缺省 `time` 与 `self_id` 将自动补全
+    Event(Value),
+    /// 发送文本消息事件
+    Message(MessageStep),
+    /// 等待毫秒数
+    Sleep(u64),
+    /// 等待指定的 Api 调用
+    Expect(Expect),
+    /// 断言超时时间内没有指定的 Api 调用
+    ExpectNone(Expect),
+}
+
+/// 文本消息事件
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessageStep {
+    /// 发送者 QQ 号
+    pub user_id: String,
+    /// 群号，为空时为私聊消息
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// 消息文本
+    pub text: String,
+    /// 群角色 owner|admin|member
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// Api 调用断言
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Expect {
+    /// Api 名称，如 `send_group_msg`
+    pub action: String,
+    /// params 需包含的字段
+    #[serde(default)]
+    pub params: Option<Value>,
+    /// 发送消息的文本内容（拼接所有 text 消息段）
+    #[serde(default)]
+    pub text: Option<String>,
+    /// 超时毫秒数
+    #[serde(default = "default_timeout")]
+    pub timeout_ms: u64,
+}
+
+impl Scenario {
+    /// 按扩展名读取 YAML 或 JSON 剧本
+    pub fn load(path: &Path) -> Result<Self, super::Error> {
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(serde_json::from_str(&content)?),
+            _ => Ok(serde_yaml::from_str(&content)?),
+        }
+    }
+
+    /// 生成 action 对应的 ApiResp
+    pub fn response(&self, action: &str, echo: &Value) -> Value {
+        let mut resp = self
+            .responses
+            .get(action)
+            .cloned()
+            .unwrap_or_else(|| json!({"status": "ok", "retcode": 0, "data": null}));
+        if let Value::Object(map) = &mut resp {
+            map.insert("echo".to_string(), echo.clone());
+        }
+        resp
+    }
+}
+
+impl MessageStep {
+    /// 生成完整 OneBot 消息事件
+    pub fn to_event(&self, self_id: &str, message_id: i32) -> Value {
+        let message = json!([{"type": "text", "data": {"text": self.text}}]);
+        match &self.group_id {
+            Some(group_id) => json!({
+                "post_type": "message",
+                "message_type": "group",
+                "sub_type": "normal",
+                "time": timestamp(),
+                "self_id": self_id,
+                "message_id": message_id,
+                "group_id": group_id,
+                "user_id": self.user_id,
+                "anonymous": null,
+                "message": message,
+                "raw_message": self.text,
+                "font": 0,
+                "sender": {
+                    "user_id": self.user_id,
+                    "nickname": self.user_id,
+                    "card": "",
+                    "sex": "unknown",
+                    "age": 0,
+                    "area": "",
+                    "level": "",
+                    "role": self.role.as_deref().unwrap_or("member"),
+                    "title": ""
+                }
+            }),
+            None => json!({
+                "post_type": "message",
+                "message_type": "private",
+                "sub_type": "friend",
+                "time": timestamp(),
+                "self_id": self_id,
+                "message_id": message_id,
+                "user_id": self.user_id,
+                "message": message,
+                "raw_message": self.text,
+                "font": 0,
+                "sender": {
+                    "user_id": self.user_id,
+                    "nickname": self.user_id,
+                    "sex": "unknown",
+                    "age": 0
+                }
+            }),
+        }
+    }
+}
+
+impl Expect {
+    /// 判定 Api 调用是否满足断言
+    pub fn matches(&self, call: &super::RecordedCall) -> bool {
+        if call.action != self.action {
+            return false;
+        }
+        if let Some(params) = &self.params {
+            if !contains(&call.params, params) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.text {
+            if &message_text(&call.params["message"]) != text {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 补全事件缺省字段
+pub fn fill_event(mut event: Value, self_id: &str) -> Value {
+    if let Value::Object(map) = &mut event {
+        map.entry("time").or_insert_with(|| json!(timestamp()));
+        map.entry("self_id").or_insert_with(|| json!(self_id));
+    }
+    event
+}
+
+/// 拼接消息中的文本，兼容字符串与数组格式
+pub fn message_text(message: &Value) -> String {
+    match message {
+        Value::String(s) => s.clone(),
+        Value::Array(segments) => segments
+            .iter()
+            .filter(|s| s["type"] == "text")
+            .filter_map(|s| s["data"]["text"].as_str())
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+/// actual 是否包含 expected 中的所有字段
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(a), Value::Object(e)) => e
+            .iter()
+            .all(|(k, v)| a.get(k).map(|av| contains(av, v)).unwrap_or(false)),
+        // OneBot 实现中 id 常以数字与字符串混用
+        (Value::Number(n), Value::String(s)) | (Value::String(s), Value::Number(n)) => {
+            n.to_string() == *s
+        }
+        _ => actual == expected,
+    }
+}
+
+fn timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}